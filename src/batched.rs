@@ -0,0 +1,155 @@
+use std::{cmp, mem};
+use std::time::{Duration, Instant};
+use futures::{Async, AsyncSink, Future, Sink};
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Collects items into batches and sends them to a sink of `Vec`s. A batch is sent once it
+/// contains `max_items` items or once `max_delay` has passed since its first item was added.
+/// Created using `SinkExt::batched`.
+///
+/// `poll_complete` only resolves once every batch has been sent, so it may take up to `max_delay`
+/// to resolve. `close` sends the current batch immediately.
+pub struct Batched<S, T> {
+    inner: S,
+    max_items: usize,
+    max_delay: Duration,
+    batch: Vec<T>,
+    delay: Option<Delay>,
+}
+
+impl<S, T> Batched<S, T>
+where
+    S: Sink<SinkItem=Vec<T>>,
+{
+    pub fn new(inner: S, max_items: usize, max_delay: Duration) -> Batched<S, T> {
+        Batched {
+            inner,
+            max_items: cmp::max(max_items, 1),
+            max_delay,
+            batch: Vec::new(),
+            delay: None,
+        }
+    }
+
+    /// Unpack the `Batched`, returning the inner sink along with any items which have not been
+    /// sent to it yet.
+    pub fn into_inner(self) -> (S, Vec<T>) {
+        (self.inner, self.batch)
+    }
+
+    /// Try to start sending the current batch to the inner sink.
+    fn send_batch(&mut self) -> Result<Async<()>, S::SinkError> {
+        let batch = mem::take(&mut self.batch);
+        match self.inner.start_send(batch)? {
+            AsyncSink::Ready => {
+                self.delay = None;
+                Ok(Async::Ready(()))
+            },
+            AsyncSink::NotReady(batch) => {
+                self.batch = batch;
+                Ok(Async::NotReady)
+            },
+        }
+    }
+
+    /// Check whether the current batch is full or has timed out.
+    fn batch_due(&mut self) -> bool {
+        if self.batch.len() >= self.max_items {
+            return true;
+        }
+        match self.delay {
+            Some(ref mut delay) => delay.poll().void_unwrap().is_ready(),
+            None => false,
+        }
+    }
+}
+
+impl<S, T> Sink for Batched<S, T>
+where
+    S: Sink<SinkItem=Vec<T>>,
+{
+    type SinkItem = T;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: T) -> Result<AsyncSink<T>, S::SinkError> {
+        if self.batch.len() >= self.max_items {
+            if let Async::NotReady = self.send_batch()? {
+                return Ok(AsyncSink::NotReady(item));
+            }
+        }
+
+        if self.batch.is_empty() {
+            self.delay = Some(Delay::new(Instant::now() + self.max_delay));
+        }
+        self.batch.push(item);
+        if self.batch.len() >= self.max_items {
+            let _ = self.send_batch()?;
+        }
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, S::SinkError> {
+        if !self.batch.is_empty() {
+            if !self.batch_due() {
+                let _ = self.inner.poll_complete()?;
+                return Ok(Async::NotReady);
+            }
+            if let Async::NotReady = self.send_batch()? {
+                let _ = self.inner.poll_complete()?;
+                return Ok(Async::NotReady);
+            }
+        }
+
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Result<Async<()>, S::SinkError> {
+        if !self.batch.is_empty() {
+            if let Async::NotReady = self.send_batch()? {
+                let _ = self.inner.poll_complete()?;
+                return Ok(Async::NotReady);
+            }
+        }
+
+        self.inner.close()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{future, Stream};
+    use futures::sync::mpsc;
+    use tokio;
+    use SinkExt;
+
+    #[test]
+    fn batches_flushed_on_size_and_close() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let (tx, rx) = mpsc::unbounded::<Vec<u32>>();
+        let mut sink = tx.batched(3, Duration::from_secs(10));
+        unwrap!(runtime.block_on(future::lazy(|| {
+            for i in 0..7 {
+                assert!(unwrap!(sink.start_send(i)).is_ready());
+            }
+            future::poll_fn(|| sink.close())
+        })));
+        drop(sink);
+        let batches = unwrap!(runtime.block_on(rx.collect()));
+        assert_eq!(batches, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+    }
+
+    #[test]
+    fn batch_flushed_after_delay() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let (tx, rx) = mpsc::unbounded::<Vec<u32>>();
+        let max_delay = Duration::from_millis(20);
+        let started = Instant::now();
+        let sink = unwrap!(runtime.block_on(tx.batched(10, max_delay).send(1)));
+        assert!(started.elapsed() >= max_delay);
+        drop(sink);
+        let batches = unwrap!(runtime.block_on(rx.collect()));
+        assert_eq!(batches, vec![vec![1]]);
+    }
+}
@@ -0,0 +1,105 @@
+use std::{cmp, mem};
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Stream};
+use futures::stream::Fuse;
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Groups the items of a stream into chunks of at most `max_items` items. A partial chunk is
+/// yielded once `max_delay` has passed since its first item arrived, or when the stream ends.
+/// Created using `StreamExt::chunks_timeout`.
+pub struct ChunksTimeout<S: Stream> {
+    stream: Fuse<S>,
+    max_items: usize,
+    max_delay: Duration,
+    chunk: Vec<S::Item>,
+    delay: Option<Delay>,
+}
+
+impl<S: Stream> ChunksTimeout<S> {
+    pub fn new(stream: S, max_items: usize, max_delay: Duration) -> ChunksTimeout<S> {
+        ChunksTimeout {
+            stream: stream.fuse(),
+            max_items: cmp::max(max_items, 1),
+            max_delay,
+            chunk: Vec::new(),
+            delay: None,
+        }
+    }
+
+    fn take_chunk(&mut self) -> Vec<S::Item> {
+        self.delay = None;
+        mem::take(&mut self.chunk)
+    }
+}
+
+impl<S: Stream> Stream for ChunksTimeout<S> {
+    type Item = Vec<S::Item>;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<Vec<S::Item>>>, S::Error> {
+        loop {
+            match self.stream.poll()? {
+                Async::Ready(Some(item)) => {
+                    if self.chunk.is_empty() {
+                        self.delay = Some(Delay::new(Instant::now() + self.max_delay));
+                    }
+                    self.chunk.push(item);
+                    if self.chunk.len() >= self.max_items {
+                        return Ok(Async::Ready(Some(self.take_chunk())));
+                    }
+                },
+                Async::Ready(None) => {
+                    if self.chunk.is_empty() {
+                        return Ok(Async::Ready(None));
+                    }
+                    return Ok(Async::Ready(Some(self.take_chunk())));
+                },
+                Async::NotReady => {
+                    let expired = match self.delay {
+                        Some(ref mut delay) => delay.poll().void_unwrap().is_ready(),
+                        None => false,
+                    };
+                    if expired {
+                        return Ok(Async::Ready(Some(self.take_chunk())));
+                    }
+                    return Ok(Async::NotReady);
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::stream;
+    use tokio;
+    use StreamExt;
+
+    #[test]
+    fn chunks_by_size_and_stream_end() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let stream = stream::iter_ok::<_, ()>(0..7).chunks_timeout(3, Duration::from_secs(10));
+        let chunks = unwrap!(runtime.block_on(stream.collect()));
+        assert_eq!(chunks, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+    }
+
+    #[test]
+    fn partial_chunk_after_delay() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let max_delay = Duration::from_millis(20);
+        let stream = {
+            stream::iter_ok::<_, ()>(vec![1, 2])
+            .chain(stream::poll_fn(|| Ok(Async::NotReady)))
+            .chunks_timeout(10, max_delay)
+        };
+        let started = Instant::now();
+        let (chunk, _stream) = match runtime.block_on(stream.into_future()) {
+            Ok(x) => x,
+            Err(..) => panic!("stream failed"),
+        };
+        assert_eq!(chunk, Some(vec![1, 2]));
+        assert!(started.elapsed() >= max_delay);
+    }
+}
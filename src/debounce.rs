@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Stream};
+use futures::stream::Fuse;
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Yields an item from a stream only once the stream has gone quiet for a given duration, dropping
+/// any items which are followed by another item within that duration. Created using
+/// `StreamExt::debounce`.
+pub struct Debounce<S: Stream> {
+    stream: Fuse<S>,
+    duration: Duration,
+    delay: Delay,
+    pending: Option<S::Item>,
+}
+
+impl<S: Stream> Debounce<S> {
+    pub fn new(stream: S, duration: Duration) -> Debounce<S> {
+        Debounce {
+            stream: stream.fuse(),
+            duration,
+            delay: Delay::new(Instant::now() + duration),
+            pending: None,
+        }
+    }
+}
+
+impl<S: Stream> Stream for Debounce<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<S::Item>>, S::Error> {
+        loop {
+            match self.stream.poll()? {
+                Async::Ready(Some(item)) => {
+                    self.delay.reset(Instant::now() + self.duration);
+                    self.pending = Some(item);
+                },
+                Async::Ready(None) => return Ok(Async::Ready(self.pending.take())),
+                Async::NotReady => {
+                    if self.pending.is_none() {
+                        return Ok(Async::NotReady);
+                    }
+                    return match self.delay.poll().void_unwrap() {
+                        Async::Ready(()) => Ok(Async::Ready(self.pending.take())),
+                        Async::NotReady => Ok(Async::NotReady),
+                    };
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio;
+    use test_util::timed_stream;
+    use StreamExt;
+
+    #[test]
+    fn yields_items_after_quiet_period() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Instant::now();
+        let items = [(0, 1), (5, 2), (100, 3), (200, 4)];
+        let stream = {
+            timed_stream::<_, ()>(started, &items)
+            .debounce(Duration::from_millis(30))
+        };
+        assert_eq!(unwrap!(runtime.block_on(stream.collect())), vec![2, 3, 4]);
+    }
+}
@@ -0,0 +1,225 @@
+use std::mem;
+use futures::{Async, Future, Stream};
+use void::{ResultVoidExt, Void};
+use mpsc::{self, UnboundedSender, UnboundedReceiver, SendError};
+use while_driving::FinishInner;
+
+/// A growable set of futures which can be driven alongside another future using
+/// `FutureExt::while_driving_set`. Created using `drive_set`.
+///
+/// `DriveSet` is itself a future which drives all the futures in the set and resolves to their
+/// results, in the order they were added, once every `DriveSetHandle` has been dropped and all the
+/// futures have finished. Until then futures can still be added to it.
+pub struct DriveSet<B: Future> {
+    futures: Vec<FinishInner<B>>,
+    rx: UnboundedReceiver<B>,
+}
+
+/// Handle used to add futures to a `DriveSet`, including while it is being driven. Created using
+/// `drive_set`.
+#[derive(Debug)]
+pub struct DriveSetHandle<B> {
+    tx: UnboundedSender<B>,
+}
+
+impl<B> Clone for DriveSetHandle<B> {
+    fn clone(&self) -> DriveSetHandle<B> {
+        DriveSetHandle {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+/// Create an empty `DriveSet` along with a handle which can be used to add futures to it.
+pub fn drive_set<B: Future>() -> (DriveSetHandle<B>, DriveSet<B>) {
+    let (tx, rx) = mpsc::unbounded();
+    let handle = DriveSetHandle {
+        tx,
+    };
+    let set = DriveSet {
+        futures: Vec::new(),
+        rx,
+    };
+    (handle, set)
+}
+
+impl<B> DriveSetHandle<B> {
+    /// Add a future to the set. Fails if the `DriveSet` has been dropped or unpacked.
+    pub fn drive(&self, future: B) -> Result<(), SendError<B>> {
+        self.tx.unbounded_send(future)
+    }
+}
+
+impl<B: Future> DriveSet<B> {
+    /// Add a future to the set.
+    pub fn push(&mut self, future: B) {
+        self.futures.push(FinishInner::Running(future));
+    }
+
+    /// Unpack the `DriveSet`, returning every future added to it, in the order they were added,
+    /// either still running or along with the result it finished with.
+    pub fn into_inner(mut self) -> Vec<FinishInner<B>> {
+        self.rx.close();
+        while let Async::Ready(Some(future)) = self.rx.poll().void_unwrap() {
+            self.push(future);
+        }
+        self.futures
+    }
+
+    /// Poll all the running futures, returning `true` if they have all finished and no more can
+    /// be added.
+    fn drive(&mut self) -> bool {
+        let mut finished = true;
+        loop {
+            match self.rx.poll().void_unwrap() {
+                Async::Ready(Some(future)) => self.push(future),
+                Async::Ready(None) => break,
+                Async::NotReady => {
+                    finished = false;
+                    break;
+                },
+            }
+        }
+
+        for inner in &mut self.futures {
+            let res = match *inner {
+                FinishInner::Running(ref mut future) => {
+                    match future.poll() {
+                        Ok(Async::Ready(x)) => Ok(x),
+                        Ok(Async::NotReady) => {
+                            finished = false;
+                            continue;
+                        },
+                        Err(e) => Err(e),
+                    }
+                },
+                FinishInner::Ran(..) => continue,
+            };
+            *inner = FinishInner::Ran(res);
+        }
+        finished
+    }
+}
+
+impl<B: Future> Future for DriveSet<B> {
+    type Item = Vec<Result<B::Item, B::Error>>;
+    type Error = Void;
+
+    fn poll(&mut self) -> Result<Async<Vec<Result<B::Item, B::Error>>>, Void> {
+        if !self.drive() {
+            return Ok(Async::NotReady);
+        }
+
+        let results = {
+            mem::take(&mut self.futures)
+            .into_iter()
+            .map(|inner| match inner {
+                FinishInner::Ran(res) => res,
+                FinishInner::Running(..) => unreachable!(),
+            })
+            .collect()
+        };
+        Ok(Async::Ready(results))
+    }
+}
+
+/// Resolves a future while driving a `DriveSet` of futures in parallel. Created using
+/// `FutureExt::while_driving_set`.
+pub struct WhileDrivingSet<A, B: Future> {
+    inner: Option<(A, DriveSet<B>)>,
+}
+
+impl<A, B: Future> WhileDrivingSet<A, B> {
+    pub fn new(a: A, set: DriveSet<B>) -> WhileDrivingSet<A, B> {
+        WhileDrivingSet {
+            inner: Some((a, set)),
+        }
+    }
+}
+
+impl<A, B> Future for WhileDrivingSet<A, B>
+where
+    A: Future,
+    B: Future,
+{
+    type Item = (A::Item, DriveSet<B>);
+    type Error = (A::Error, DriveSet<B>);
+
+    fn poll(&mut self) -> Result<Async<(A::Item, DriveSet<B>)>, (A::Error, DriveSet<B>)> {
+        let (mut a, mut set) = match self.inner.take() {
+            Some(inner) => inner,
+            None => panic!("poll() called on WhileDrivingSet which has already finished"),
+        };
+        match a.poll() {
+            Ok(Async::Ready(x)) => Ok(Async::Ready((x, set))),
+            Ok(Async::NotReady) => {
+                set.drive();
+                self.inner = Some((a, set));
+                Ok(Async::NotReady)
+            },
+            Err(e) => Err((e, set)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    use futures::future;
+    use tokio;
+    use delay::Delay;
+    use FutureExt;
+    use BoxFuture;
+
+    #[test]
+    fn futures_added_while_driving() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let (handle, set) = drive_set::<BoxFuture<u32, u32>>();
+        let driven = Rc::new(Cell::new(false));
+
+        let driven_cloned = driven.clone();
+        let background = {
+            Delay::new(Instant::now() + Duration::from_millis(10))
+            .then(move |_| {
+                driven_cloned.set(true);
+                Ok(1)
+            })
+            .into_boxed()
+        };
+        let main = {
+            future::lazy(move || {
+                unwrap!(handle.drive(background));
+                Delay::new(Instant::now() + Duration::from_millis(30))
+                .map(move |()| handle)
+                .infallible::<()>()
+            })
+            .while_driving_set(set)
+        };
+        let (handle, set) = match runtime.block_on(main) {
+            Ok(x) => x,
+            Err(..) => panic!("main future failed"),
+        };
+        assert!(driven.get());
+
+        unwrap!(handle.drive(future::err(2).into_boxed()));
+        drop(handle);
+        let results = unwrap!(runtime.block_on(set));
+        assert_eq!(results, vec![Ok(1), Err(2)]);
+    }
+
+    #[test]
+    fn set_waits_for_handles() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let (handle, set) = drive_set::<BoxFuture<u32, u32>>();
+        let res = runtime.block_on(set.with_timeout(Duration::from_millis(10)));
+        assert!(unwrap!(res).is_none());
+
+        let (_, set) = drive_set::<BoxFuture<u32, u32>>();
+        let results = unwrap!(runtime.block_on(set));
+        assert!(results.is_empty());
+        drop(handle);
+    }
+}
@@ -1,7 +1,7 @@
-use futures::{Async, Future, Stream};
+use futures::{Async, AsyncSink, Future, Stream, Sink};
 
-/// Wraps a stream or future and runs a callback when the stream/future ends or when `Finally` is
-/// dropped.
+/// Wraps a stream, sink or future and runs a callback when the stream/future ends, when the sink is
+/// closed, or when `Finally` is dropped.
 pub struct Finally<F, D>
 where
     D: FnOnce(),
@@ -80,3 +80,30 @@ where
     }
 }
 
+impl<S, D> Sink for Finally<S, D>
+where
+    S: Sink,
+    D: FnOnce(),
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: S::SinkItem) -> Result<AsyncSink<S::SinkItem>, S::SinkError> {
+        unwrap!(self.inner.as_mut()).future.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, S::SinkError> {
+        unwrap!(self.inner.as_mut()).future.poll_complete()
+    }
+
+    fn close(&mut self) -> Result<Async<()>, S::SinkError> {
+        let mut on_drop = unwrap!(self.inner.take());
+        match on_drop.future.close()? {
+            Async::Ready(()) => Ok(Async::Ready(())),
+            Async::NotReady => {
+                self.inner = Some(on_drop);
+                Ok(Async::NotReady)
+            },
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use futures::{Async, Future, IntoFuture};
+
+/// Runs a collection of futures in parallel and yields the value of the first to succeed. Created
+/// using `first_ok_all`.
+pub struct FirstOkAll<F: Future> {
+    futures: Vec<Option<F>>,
+    errors: Vec<Option<F::Error>>,
+    remaining: usize,
+}
+
+/// Run all the given futures in parallel and yield the value of the first one to succeed,
+/// dropping the rest. If every future fails then all the errors are returned in the same order as
+/// the futures they came from.
+pub fn first_ok_all<I>(futures: I) -> FirstOkAll<<I::Item as IntoFuture>::Future>
+where
+    I: IntoIterator,
+    I::Item: IntoFuture,
+{
+    let futures: Vec<_> = {
+        futures
+        .into_iter()
+        .map(|f| Some(f.into_future()))
+        .collect()
+    };
+    let errors = futures.iter().map(|_| None).collect();
+    let remaining = futures.len();
+    FirstOkAll {
+        futures,
+        errors,
+        remaining,
+    }
+}
+
+impl<F: Future> Future for FirstOkAll<F> {
+    type Item = F::Item;
+    type Error = Vec<F::Error>;
+
+    fn poll(&mut self) -> Result<Async<F::Item>, Vec<F::Error>> {
+        for (i, slot) in self.futures.iter_mut().enumerate() {
+            let res = match *slot {
+                Some(ref mut future) => future.poll(),
+                None => continue,
+            };
+            match res {
+                Ok(Async::Ready(x)) => {
+                    self.futures.clear();
+                    self.errors.clear();
+                    return Ok(Async::Ready(x));
+                },
+                Ok(Async::NotReady) => (),
+                Err(e) => {
+                    *slot = None;
+                    self.errors[i] = Some(e);
+                    self.remaining -= 1;
+                },
+            }
+        }
+
+        if self.remaining == 0 {
+            let errors = self.errors.drain(..).map(|e| unwrap!(e)).collect();
+            return Err(errors);
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::future;
+
+    #[test]
+    fn first_ok_all_success() {
+        let futures = vec![
+            future::result(Err(0)),
+            future::result(Ok(1)),
+            future::result(Err(2)),
+        ];
+        let res = first_ok_all(futures).wait();
+        assert_eq!(res, Ok(1));
+    }
+
+    #[test]
+    fn first_ok_all_failure() {
+        let futures = vec![
+            future::err::<(), _>(0),
+            future::err(1),
+            future::err(2),
+        ];
+        let res = first_ok_all(futures).wait();
+        assert_eq!(res, Err(vec![0, 1, 2]));
+    }
+}
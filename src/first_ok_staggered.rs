@@ -0,0 +1,193 @@
+use std::{cmp, mem};
+use std::time::{Duration, Instant};
+use futures::{Async, Future, IntoFuture, Stream};
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Adapts a stream of futures to a future by running them with staggered starts and taking the
+/// value of the first one to succeed. Created using `StreamExt::first_ok_staggered`.
+pub struct FirstOkStaggered<S>
+where
+    S: Stream,
+    S::Item: IntoFuture,
+{
+    stream: Option<S>,
+    stagger: Duration,
+    max_in_flight: usize,
+    timer: Delay,
+    start_now: bool,
+    running: Vec<(usize, <S::Item as IntoFuture>::Future)>,
+    errors: Vec<Option<S::Error>>,
+}
+
+impl<S> FirstOkStaggered<S>
+where
+    S: Stream,
+    S::Item: IntoFuture,
+{
+    pub fn new(stream: S, stagger: Duration, max_in_flight: usize) -> FirstOkStaggered<S> {
+        FirstOkStaggered {
+            stream: Some(stream),
+            stagger,
+            max_in_flight: cmp::max(max_in_flight, 1),
+            timer: Delay::new(Instant::now()),
+            start_now: true,
+            running: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<S> Future for FirstOkStaggered<S>
+where
+    S: Stream,
+    S::Item: IntoFuture<Error = S::Error>,
+{
+    type Item = <S::Item as IntoFuture>::Item;
+    type Error = Vec<S::Error>;
+
+    fn poll(&mut self) -> Result<Async<Self::Item>, Vec<S::Error>> {
+        loop {
+            let mut i = 0;
+            while i < self.running.len() {
+                match self.running[i].1.poll() {
+                    Ok(Async::Ready(x)) => {
+                        self.running.clear();
+                        self.errors.clear();
+                        return Ok(Async::Ready(x));
+                    },
+                    Ok(Async::NotReady) => i += 1,
+                    Err(e) => {
+                        let (index, _) = self.running.swap_remove(i);
+                        self.errors[index] = Some(e);
+                        self.start_now = true;
+                    },
+                }
+            }
+
+            if self.running.len() < self.max_in_flight && !self.start_now {
+                if let Async::Ready(()) = self.timer.poll().void_unwrap() {
+                    self.start_now = true;
+                }
+            }
+
+            if self.start_now && self.running.len() < self.max_in_flight {
+                let res = match self.stream {
+                    Some(ref mut stream) => stream.poll(),
+                    None => Ok(Async::NotReady),
+                };
+                match res {
+                    Ok(Async::Ready(Some(candidate))) => {
+                        let index = self.errors.len();
+                        self.errors.push(None);
+                        self.running.push((index, candidate.into_future()));
+                        self.timer.reset(Instant::now() + self.stagger);
+                        self.start_now = false;
+                        continue;
+                    },
+                    Ok(Async::Ready(None)) => {
+                        self.stream = None;
+                    },
+                    Ok(Async::NotReady) => (),
+                    Err(e) => {
+                        self.errors.push(Some(e));
+                        continue;
+                    },
+                }
+            }
+
+            if self.stream.is_none() && self.running.is_empty() {
+                let errors = mem::take(&mut self.errors);
+                return Err(errors.into_iter().map(|e| unwrap!(e)).collect());
+            }
+            return Ok(Async::NotReady);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{future, stream};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use tokio;
+    use BoxFuture;
+    use FutureExt;
+    use StreamExt;
+
+    #[test]
+    fn candidates_are_staggered() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let stagger = Duration::from_millis(20);
+        let starts = Rc::new(RefCell::new(Vec::new()));
+        let starts_cloned = starts.clone();
+        let candidates = stream::iter_ok::<_, u32>(0..3).map(move |i| -> BoxFuture<u32, u32> {
+            starts_cloned.borrow_mut().push(Instant::now());
+            match i {
+                2 => future::ok(i).into_boxed(),
+                _ => future::empty().into_boxed(),
+            }
+        });
+        let res = runtime.block_on(candidates.first_ok_staggered(stagger, 5));
+        assert_eq!(res, Ok(2));
+        let starts = starts.borrow();
+        assert_eq!(starts.len(), 3);
+        assert!(starts[1] - starts[0] >= stagger);
+        assert!(starts[2] - starts[1] >= stagger);
+    }
+
+    #[test]
+    fn max_in_flight_is_respected() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Rc::new(RefCell::new(0));
+        let started_cloned = started.clone();
+        let candidates = stream::iter_ok::<_, u32>(0..3).map(move |i| -> BoxFuture<u32, u32> {
+            *started_cloned.borrow_mut() += 1;
+            match i {
+                2 => future::ok(i).into_boxed(),
+                _ => future::empty().into_boxed(),
+            }
+        });
+        let res = runtime.block_on({
+            candidates
+            .first_ok_staggered(Duration::from_millis(1), 2)
+            .with_timeout(Duration::from_millis(50))
+        });
+        assert_eq!(res, Ok(None));
+        assert_eq!(*started.borrow(), 2);
+    }
+
+    #[test]
+    fn next_candidate_started_after_failure() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Instant::now();
+        let candidates = stream::iter_ok::<_, u32>(0..4).map(|i| {
+            if i == 3 {
+                Ok(i)
+            } else {
+                Err(i)
+            }
+        });
+        let res = runtime.block_on(candidates.first_ok_staggered(Duration::from_secs(10), 1));
+        assert_eq!(res, Ok(3));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn errors_in_stream_order() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let candidates = stream::iter_ok::<_, u32>(0..2).map(|i| -> BoxFuture<u32, u32> {
+            match i {
+                0 => {
+                    Delay::new(Instant::now() + Duration::from_millis(50))
+                    .then(|_| Err(0))
+                    .into_boxed()
+                },
+                _ => future::err(i).into_boxed(),
+            }
+        });
+        let res = runtime.block_on(candidates.first_ok_staggered(Duration::from_millis(10), 2));
+        assert_eq!(res, Err(vec![0, 1]));
+    }
+}
@@ -1,7 +1,9 @@
 use bytes::{Bytes, BytesMut};
 use tokio::io::{AsyncRead, AsyncWrite};
 use futures::{Stream, Sink, Async, AsyncSink};
-use std::{io, mem};
+use futures::sync::BiLock;
+use std::{fmt, io, mem};
+use std::error::Error;
 
 fn zeros(n: usize) -> BytesMut {
     let mut ret = BytesMut::with_capacity(n);
@@ -14,27 +16,361 @@ fn zeros(n: usize) -> BytesMut {
     ret
 }
 
+/// The maximum length of a length prefix. A LEB128 varint encoding of a `u64` takes at most ten
+/// bytes.
+const MAX_HEADER_LEN: usize = 10;
+
 /// An alternative to tokio_io's `Framed` which doesn't internally buffer data.
 /// This gives it much lower performance but means that you can use `.into_inner()` without losing
 /// data.
+///
+/// By default each frame is prefixed with its length as a 4-byte big-endian integer. Use
+/// `FramedUnbuffered::builder` to select a different length-prefix format.
 pub struct FramedUnbuffered<T> {
     stream: T,
+    config: FramedUnbufferedBuilder,
     read_state: ReadState,
     write_state: WriteState,
 }
 
-impl<T> FramedUnbuffered<T> {
-    pub fn new(stream: T) -> FramedUnbuffered<T> {
+/// Configures the length-prefix format of a `FramedUnbuffered`. Created using
+/// `FramedUnbuffered::builder`.
+#[derive(Debug, Clone, Copy)]
+pub struct FramedUnbufferedBuilder {
+    prefix_width: u8,
+    little_endian: bool,
+    varint: bool,
+    length_includes_header: bool,
+    max_frame_length: usize,
+}
+
+impl Default for FramedUnbufferedBuilder {
+    fn default() -> FramedUnbufferedBuilder {
+        FramedUnbufferedBuilder {
+            prefix_width: 4,
+            little_endian: false,
+            varint: false,
+            length_includes_header: false,
+            max_frame_length: usize::MAX,
+        }
+    }
+}
+
+impl FramedUnbufferedBuilder {
+    /// Use a fixed-width length prefix of `width` bytes. `width` must be between 1 and 8.
+    pub fn prefix_width(mut self, width: u8) -> FramedUnbufferedBuilder {
+        assert!((1..=8).contains(&width), "invalid length-prefix width {}", width);
+        self.prefix_width = width;
+        self.varint = false;
+        self
+    }
+
+    /// Encode a fixed-width length prefix in big-endian byte order. This is the default.
+    pub fn big_endian(mut self) -> FramedUnbufferedBuilder {
+        self.little_endian = false;
+        self.varint = false;
+        self
+    }
+
+    /// Encode a fixed-width length prefix in little-endian byte order.
+    pub fn little_endian(mut self) -> FramedUnbufferedBuilder {
+        self.little_endian = true;
+        self.varint = false;
+        self
+    }
+
+    /// Encode the length prefix as an unsigned LEB128 varint rather than a fixed-width integer.
+    pub fn varint(mut self) -> FramedUnbufferedBuilder {
+        self.varint = true;
+        self
+    }
+
+    /// Whether the length in the prefix counts the bytes of the prefix itself as well as the
+    /// frame data. Defaults to `false`.
+    pub fn length_includes_header(mut self, includes: bool) -> FramedUnbufferedBuilder {
+        self.length_includes_header = includes;
+        self
+    }
+
+    /// The maximum length of the data of a single frame. Receiving a longer frame fails the
+    /// stream with `io::ErrorKind::InvalidData` before any memory is allocated for it, and sending
+    /// one fails with `io::ErrorKind::InvalidInput`. By default frame lengths are only limited by
+    /// what the length prefix can encode.
+    pub fn max_frame_length(mut self, max: usize) -> FramedUnbufferedBuilder {
+        self.max_frame_length = max;
+        self
+    }
+
+    /// Create a `FramedUnbuffered` which uses this length-prefix format.
+    pub fn build<T>(self, stream: T) -> FramedUnbuffered<T> {
         FramedUnbuffered {
             stream,
-            read_state: ReadState::ReadingSize {
-                bytes_read: 0,
-                size_buffer: [0u8; 4],
-            },
+            config: self,
+            read_state: ReadState::new(),
             write_state: WriteState::WaitingForInput,
         }
     }
 
+    /// Resume framing on a stream using the parts returned by `FramedUnbuffered::into_parts`.
+    /// Fails if `read_buffer` is not a valid prefix of a single frame in this format, in which case
+    /// the parts are handed back along with the error.
+    pub fn build_from_parts<T>(
+        self,
+        parts: FramedUnbufferedParts<T>,
+    ) -> Result<FramedUnbuffered<T>, (io::Error, FramedUnbufferedParts<T>)> {
+        let read_state = match self.read_state_from_buffer(&parts.read_buffer) {
+            Ok(read_state) => read_state,
+            Err(e) => return Err((e, parts)),
+        };
+        let FramedUnbufferedParts { stream, write_buffer, .. } = parts;
+
+        let write_state = if write_buffer.is_empty() {
+            WriteState::WaitingForInput
+        } else {
+            WriteState::WritingData {
+                data_buffer: write_buffer,
+                bytes_written: 0,
+            }
+        };
+
+        Ok(FramedUnbuffered {
+            stream,
+            config: self,
+            read_state,
+            write_state,
+        })
+    }
+
+    /// The read state of a stream which has already had the bytes in `read_buffer` read from it.
+    fn read_state_from_buffer(&self, read_buffer: &[u8]) -> io::Result<ReadState> {
+        let mut read_state = ReadState::new();
+        for (i, byte) in read_buffer.iter().enumerate() {
+            if let ReadState::ReadingSize { ref mut bytes_read, ref mut size_buffer } = read_state {
+                size_buffer[*bytes_read as usize] = *byte;
+                *bytes_read += 1;
+                let header = &size_buffer[..(*bytes_read as usize)];
+                let len = match self.decode_header(header)? {
+                    Some(len) => len,
+                    None => continue,
+                };
+                let data = &read_buffer[(i + 1)..];
+                if data.len() > len {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "read buffer contains more than one frame",
+                    ));
+                }
+                let mut data_buffer = zeros(len);
+                data_buffer[..data.len()].copy_from_slice(data);
+                read_state = ReadState::ReadingData {
+                    size_buffer: *size_buffer,
+                    size_len: *bytes_read,
+                    bytes_read: data.len(),
+                    data_buffer,
+                };
+            }
+            if let ReadState::ReadingData { .. } = read_state {
+                break;
+            }
+        }
+        Ok(read_state)
+    }
+
+    /// The number of bytes to try to read next, given that `bytes_read` bytes of the prefix have
+    /// been read so far. Varints are read a byte at a time so that we never read past the end of
+    /// the prefix.
+    fn header_bytes_wanted(&self, bytes_read: usize) -> usize {
+        if self.varint {
+            1
+        } else {
+            self.prefix_width as usize - bytes_read
+        }
+    }
+
+    /// Decode a (possibly incomplete) length prefix. Returns `None` if more bytes are needed,
+    /// otherwise the length of the frame data.
+    fn decode_header(&self, header: &[u8]) -> io::Result<Option<usize>> {
+        let value = if self.varint {
+            let last = header[header.len() - 1];
+            if last & 0x80 != 0 {
+                if header.len() == MAX_HEADER_LEN {
+                    return Err(invalid_data("varint length prefix is too long"));
+                }
+                return Ok(None);
+            }
+            if header.len() == MAX_HEADER_LEN && last > 1 {
+                return Err(invalid_data("varint length prefix overflows a u64"));
+            }
+            header
+                .iter()
+                .enumerate()
+                .fold(0u64, |acc, (i, b)| acc | (u64::from(b & 0x7f) << (7 * i)))
+        } else {
+            if header.len() < self.prefix_width as usize {
+                return Ok(None);
+            }
+            if self.little_endian {
+                header.iter().rev().fold(0u64, |acc, b| (acc << 8) | u64::from(*b))
+            } else {
+                header.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b))
+            }
+        };
+        let len = if self.length_includes_header {
+            match value.checked_sub(header.len() as u64) {
+                Some(len) => len,
+                None => return Err(invalid_data("length prefix is shorter than the prefix itself")),
+            }
+        } else {
+            value
+        };
+        if len > self.max_frame_length as u64 {
+            return Err(invalid_data("frame exceeds the maximum frame length"));
+        }
+        Ok(Some(len as usize))
+    }
+
+    /// Encode the length prefix for a frame of `len` bytes. Returns the prefix buffer and the
+    /// number of bytes of it which are used.
+    fn encode_header(&self, len: usize) -> io::Result<([u8; MAX_HEADER_LEN], u8)> {
+        if len > self.max_frame_length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame exceeds the maximum frame length",
+            ));
+        }
+        let mut header = [0u8; MAX_HEADER_LEN];
+        let len = len as u64;
+        if self.varint {
+            let mut value = len;
+            if self.length_includes_header {
+                // The length of the prefix depends on the value being encoded, so find a prefix
+                // length which is large enough to encode itself plus the data.
+                let mut header_len = varint_len(len);
+                loop {
+                    value = match len.checked_add(header_len as u64) {
+                        Some(value) => value,
+                        None => return Err(frame_too_long()),
+                    };
+                    let needed = varint_len(value);
+                    if needed == header_len {
+                        break;
+                    }
+                    header_len = needed;
+                }
+            }
+            let mut header_len = 0;
+            loop {
+                let byte = (value & 0x7f) as u8;
+                value >>= 7;
+                if value == 0 {
+                    header[header_len] = byte;
+                    header_len += 1;
+                    break;
+                }
+                header[header_len] = byte | 0x80;
+                header_len += 1;
+            }
+            Ok((header, header_len as u8))
+        } else {
+            let width = self.prefix_width as usize;
+            let value = if self.length_includes_header {
+                match len.checked_add(width as u64) {
+                    Some(value) => value,
+                    None => return Err(frame_too_long()),
+                }
+            } else {
+                len
+            };
+            if width < 8 && value >> (8 * width) != 0 {
+                return Err(frame_too_long());
+            }
+            for i in 0..width {
+                let byte = (value >> (8 * i)) as u8;
+                if self.little_endian {
+                    header[i] = byte;
+                } else {
+                    header[width - 1 - i] = byte;
+                }
+            }
+            Ok((header, width as u8))
+        }
+    }
+}
+
+fn varint_len(value: u64) -> usize {
+    let mut len = 1;
+    let mut value = value >> 7;
+    while value != 0 {
+        len += 1;
+        value >>= 7;
+    }
+    len
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn frame_too_long() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "frame is too long for the length prefix")
+}
+
+impl FramedUnbuffered<()> {
+    /// Create a builder for configuring the length-prefix format of a `FramedUnbuffered`.
+    pub fn builder() -> FramedUnbufferedBuilder {
+        FramedUnbufferedBuilder::default()
+    }
+}
+
+impl<T> FramedUnbuffered<T> {
+    pub fn new(stream: T) -> FramedUnbuffered<T> {
+        FramedUnbufferedBuilder::default().build(stream)
+    }
+
+    /// Resume framing on a stream using the parts returned by `into_parts`, using the default
+    /// length-prefix format. Use `FramedUnbufferedBuilder::build_from_parts` for other formats.
+    /// On failure the parts are handed back along with the error.
+    pub fn from_parts(
+        parts: FramedUnbufferedParts<T>,
+    ) -> Result<FramedUnbuffered<T>, (io::Error, FramedUnbufferedParts<T>)> {
+        FramedUnbufferedBuilder::default().build_from_parts(parts)
+    }
+
+    /// Unwrap the underlying stream along with any partially read or written frame. Unlike
+    /// `into_inner` this always succeeds.
+    pub fn into_parts(self) -> FramedUnbufferedParts<T> {
+        let read_buffer = match self.read_state {
+            ReadState::Invalid => BytesMut::new(),
+            ReadState::ReadingSize { bytes_read, size_buffer } => {
+                BytesMut::from(&size_buffer[..(bytes_read as usize)])
+            },
+            ReadState::ReadingData { size_buffer, size_len, bytes_read, data_buffer } => {
+                let mut read_buffer = BytesMut::with_capacity(size_len as usize + bytes_read);
+                read_buffer.extend_from_slice(&size_buffer[..(size_len as usize)]);
+                read_buffer.extend_from_slice(&data_buffer[..bytes_read]);
+                read_buffer
+            },
+        };
+        let write_buffer = match self.write_state {
+            WriteState::Invalid | WriteState::WaitingForInput => Bytes::new(),
+            WriteState::WritingSize { size_buffer, size_len, data_buffer, bytes_written } => {
+                let range = (bytes_written as usize)..(size_len as usize);
+                let mut write_buffer = BytesMut::with_capacity(range.len() + data_buffer.len());
+                write_buffer.extend_from_slice(&size_buffer[range]);
+                write_buffer.extend_from_slice(&data_buffer);
+                write_buffer.freeze()
+            },
+            WriteState::WritingData { data_buffer, bytes_written } => {
+                data_buffer.slice_from(bytes_written)
+            },
+        };
+        FramedUnbufferedParts {
+            stream: self.stream,
+            read_buffer,
+            write_buffer,
+        }
+    }
+
     pub fn into_inner(self) -> Option<T> {
         if let ReadState::ReadingSize { bytes_read: 0, .. } = self.read_state {
             if let WriteState::WaitingForInput = self.write_state {
@@ -43,31 +379,182 @@ impl<T> FramedUnbuffered<T> {
         }
         None
     }
+
+    /// Split into a `Stream` half and a `Sink` half which can be used from separate tasks. The
+    /// halves can be put back together with `FramedUnbufferedReadHalf::reunite`.
+    pub fn split(self) -> (FramedUnbufferedReadHalf<T>, FramedUnbufferedWriteHalf<T>) {
+        let (read_stream, write_stream) = BiLock::new(self.stream);
+        let read_half = FramedUnbufferedReadHalf {
+            stream: read_stream,
+            config: self.config,
+            read_state: self.read_state,
+        };
+        let write_half = FramedUnbufferedWriteHalf {
+            stream: write_stream,
+            config: self.config,
+            write_state: self.write_state,
+        };
+        (read_half, write_half)
+    }
+}
+
+/// The underlying stream of a `FramedUnbuffered` along with any partially read or written frame.
+/// Created using `FramedUnbuffered::into_parts`.
+#[derive(Debug)]
+pub struct FramedUnbufferedParts<T> {
+    /// The underlying stream.
+    pub stream: T,
+    /// The bytes of a partially read frame, including its length prefix, which have already been
+    /// read from the stream.
+    pub read_buffer: BytesMut,
+    /// The remainder of a partially written frame, including any unwritten part of its length
+    /// prefix, which has not yet been written to the stream.
+    pub write_buffer: Bytes,
+}
+
+/// The receiving half of a `FramedUnbuffered`. Created using `FramedUnbuffered::split`.
+pub struct FramedUnbufferedReadHalf<T> {
+    stream: BiLock<T>,
+    config: FramedUnbufferedBuilder,
+    read_state: ReadState,
+}
+
+/// The sending half of a `FramedUnbuffered`. Created using `FramedUnbuffered::split`.
+pub struct FramedUnbufferedWriteHalf<T> {
+    stream: BiLock<T>,
+    config: FramedUnbufferedBuilder,
+    write_state: WriteState,
+}
+
+impl<T> FramedUnbufferedReadHalf<T> {
+    /// Put the two halves of a `FramedUnbuffered` back together. Fails if the halves did not
+    /// come from the same call to `split`.
+    pub fn reunite(
+        self,
+        write_half: FramedUnbufferedWriteHalf<T>,
+    ) -> Result<FramedUnbuffered<T>, ReuniteError<T>> {
+        let FramedUnbufferedReadHalf { stream: read_stream, config, read_state } = self;
+        let FramedUnbufferedWriteHalf { stream: write_stream, write_state, .. } = write_half;
+        match read_stream.reunite(write_stream) {
+            Ok(stream) => Ok(FramedUnbuffered {
+                stream,
+                config,
+                read_state,
+                write_state,
+            }),
+            Err(e) => {
+                let read_half = FramedUnbufferedReadHalf {
+                    stream: e.0,
+                    config,
+                    read_state,
+                };
+                let write_half = FramedUnbufferedWriteHalf {
+                    stream: e.1,
+                    config,
+                    write_state,
+                };
+                Err(ReuniteError(Box::new(read_half), Box::new(write_half)))
+            },
+        }
+    }
+}
+
+/// Error returned by `FramedUnbufferedReadHalf::reunite` when the halves did not come from the
+/// same `FramedUnbuffered`. Contains the halves that were passed in.
+pub struct ReuniteError<T>(
+    pub Box<FramedUnbufferedReadHalf<T>>,
+    pub Box<FramedUnbufferedWriteHalf<T>>,
+);
+
+impl<T> fmt::Debug for ReuniteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ReuniteError").field(&"...").finish()
+    }
+}
+
+impl<T> fmt::Display for ReuniteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "tried to reunite halves of different FramedUnbuffereds")
+    }
+}
+
+impl<T> Error for ReuniteError<T> {
+    fn description(&self) -> &str {
+        "tried to reunite halves of different FramedUnbuffereds"
+    }
+}
+
+impl<T> Stream for FramedUnbufferedReadHalf<T>
+where
+    T: AsyncRead,
+{
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> io::Result<Async<Option<BytesMut>>> {
+        let mut stream = match self.stream.poll_lock() {
+            Async::Ready(stream) => stream,
+            Async::NotReady => return Ok(Async::NotReady),
+        };
+        poll_read(&mut *stream, &self.config, &mut self.read_state)
+    }
+}
+
+impl<T> Sink for FramedUnbufferedWriteHalf<T>
+where
+    T: AsyncWrite,
+{
+    type SinkItem = Bytes;
+    type SinkError = io::Error;
+
+    fn start_send(&mut self, data_buffer: Bytes) -> io::Result<AsyncSink<Bytes>> {
+        start_send_frame(&self.config, &mut self.write_state, data_buffer)
+    }
+
+    fn poll_complete(&mut self) -> io::Result<Async<()>> {
+        let mut stream = match self.stream.poll_lock() {
+            Async::Ready(stream) => stream,
+            Async::NotReady => return Ok(Async::NotReady),
+        };
+        poll_write(&mut *stream, &mut self.write_state)
+    }
 }
 
 enum ReadState {
     Invalid,
     ReadingSize {
         bytes_read: u8,
-        size_buffer: [u8; 4],
+        size_buffer: [u8; MAX_HEADER_LEN],
     },
     ReadingData {
-        bytes_read: u32,
+        size_buffer: [u8; MAX_HEADER_LEN],
+        size_len: u8,
+        bytes_read: usize,
         data_buffer: BytesMut,
     },
 }
 
+impl ReadState {
+    fn new() -> ReadState {
+        ReadState::ReadingSize {
+            bytes_read: 0,
+            size_buffer: [0u8; MAX_HEADER_LEN],
+        }
+    }
+}
+
 enum WriteState {
     Invalid,
     WaitingForInput,
     WritingSize {
-        size_buffer: [u8; 4],
+        size_buffer: [u8; MAX_HEADER_LEN],
+        size_len: u8,
         data_buffer: Bytes,
         bytes_written: u8,
     },
     WritingData {
         data_buffer: Bytes,
-        bytes_written: u32,
+        bytes_written: usize,
     }
 }
 
@@ -79,175 +566,397 @@ where
     type Error = io::Error;
 
     fn poll(&mut self) -> io::Result<Async<Option<BytesMut>>> {
-        loop {
-            let read_state = mem::replace(&mut self.read_state, ReadState::Invalid);
-            match read_state {
-                ReadState::Invalid => unreachable!(),
-                ReadState::ReadingSize { mut bytes_read, mut size_buffer } => {
-                    match self.stream.read(&mut size_buffer[(bytes_read as usize)..]) {
-                        Ok(n) => {
-                            if n == 0 {
-                                if bytes_read == 0 {
-                                    return Ok(Async::Ready(None));
-                                } else {
-                                    return Err(io::Error::from(io::ErrorKind::BrokenPipe));
-                                }
-                            }
-                            bytes_read += n as u8;
-                            if bytes_read == 4 {
-                                let len =
-                                    ((size_buffer[0] as u32) << 24) +
-                                    ((size_buffer[1] as u32) << 16) +
-                                    ((size_buffer[2] as u32) << 8) +
-                                    (size_buffer[3] as u32);
-                                self.read_state = ReadState::ReadingData {
+        poll_read(&mut self.stream, &self.config, &mut self.read_state)
+    }
+}
+
+impl<T> Sink for FramedUnbuffered<T>
+where
+    T: AsyncWrite,
+{
+    type SinkItem = Bytes;
+    type SinkError = io::Error;
+
+    fn start_send(&mut self, data_buffer: Bytes) -> io::Result<AsyncSink<Bytes>> {
+        start_send_frame(&self.config, &mut self.write_state, data_buffer)
+    }
+
+    fn poll_complete(&mut self) -> io::Result<Async<()>> {
+        poll_write(&mut self.stream, &mut self.write_state)
+    }
+}
+
+fn poll_read<S: AsyncRead>(
+    stream: &mut S,
+    config: &FramedUnbufferedBuilder,
+    state: &mut ReadState,
+) -> io::Result<Async<Option<BytesMut>>> {
+    loop {
+        let read_state = mem::replace(state, ReadState::Invalid);
+        match read_state {
+            ReadState::Invalid => unreachable!(),
+            ReadState::ReadingSize { mut bytes_read, mut size_buffer } => {
+                // A previous poll may have failed on an invalid length prefix. Keep failing rather
+                // than reading past it.
+                if bytes_read > 0 {
+                    if let Err(e) = config.decode_header(&size_buffer[..(bytes_read as usize)]) {
+                        *state = ReadState::ReadingSize {
+                            bytes_read, size_buffer,
+                        };
+                        return Err(e);
+                    }
+                }
+                let start = bytes_read as usize;
+                let end = start + config.header_bytes_wanted(start);
+                match stream.read(&mut size_buffer[start..end]) {
+                    Ok(0) => {
+                        *state = ReadState::ReadingSize {
+                            bytes_read, size_buffer,
+                        };
+                        if bytes_read == 0 {
+                            return Ok(Async::Ready(None));
+                        }
+                        return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+                    },
+                    Ok(n) => {
+                        bytes_read += n as u8;
+                        let header = &size_buffer[..(bytes_read as usize)];
+                        match config.decode_header(header) {
+                            Ok(Some(len)) => {
+                                *state = ReadState::ReadingData {
+                                    size_buffer,
+                                    size_len: bytes_read,
                                     bytes_read: 0,
-                                    data_buffer: zeros(len as usize),
+                                    data_buffer: zeros(len),
                                 };
-                            } else {
-                                self.read_state = ReadState::ReadingSize {
+                            },
+                            Ok(None) => {
+                                *state = ReadState::ReadingSize {
                                     bytes_read, size_buffer,
                                 };
-                            }
-                        },
-                        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                            self.read_state = ReadState::ReadingSize {
-                                bytes_read, size_buffer,
-                            };
+                            },
+                            Err(e) => {
+                                *state = ReadState::ReadingSize {
+                                    bytes_read, size_buffer,
+                                };
+                                return Err(e);
+                            },
+                        }
+                    },
+                    Err(e) => {
+                        *state = ReadState::ReadingSize {
+                            bytes_read, size_buffer,
+                        };
+                        if e.kind() == io::ErrorKind::WouldBlock {
                             return Ok(Async::NotReady);
                         }
-                        Err(e) => return Err(e),
-                    }
-                },
-                ReadState::ReadingData { mut bytes_read, mut data_buffer } => {
-                    match self.stream.read(&mut data_buffer[(bytes_read as usize)..]) {
-                        Ok(n) => {
-                            if n == 0 {
-                                return Err(io::Error::from(io::ErrorKind::BrokenPipe));
-                            }
-                            bytes_read += n as u32;
-                            if bytes_read == data_buffer.len() as u32 {
-                                self.read_state = ReadState::ReadingSize {
-                                    bytes_read: 0,
-                                    size_buffer: [0u8; 4],
-                                };
-                                return Ok(Async::Ready(Some(data_buffer)));
-                            }
-                            else {
-                                self.read_state = ReadState::ReadingData {
-                                    bytes_read, data_buffer,
-                                }
-                            }
-                        },
-                        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                            self.read_state = ReadState::ReadingData {
-                                bytes_read, data_buffer,
-                            };
+                        return Err(e);
+                    },
+                }
+            },
+            ReadState::ReadingData { size_buffer, size_len, mut bytes_read, mut data_buffer } => {
+                if bytes_read == data_buffer.len() {
+                    *state = ReadState::new();
+                    return Ok(Async::Ready(Some(data_buffer)));
+                }
+                match stream.read(&mut data_buffer[bytes_read..]) {
+                    Ok(0) => {
+                        *state = ReadState::ReadingData {
+                            size_buffer, size_len, bytes_read, data_buffer,
+                        };
+                        return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+                    },
+                    Ok(n) => {
+                        bytes_read += n;
+                        *state = ReadState::ReadingData {
+                            size_buffer, size_len, bytes_read, data_buffer,
+                        };
+                    },
+                    Err(e) => {
+                        *state = ReadState::ReadingData {
+                            size_buffer, size_len, bytes_read, data_buffer,
+                        };
+                        if e.kind() == io::ErrorKind::WouldBlock {
                             return Ok(Async::NotReady);
                         }
-                        Err(e) => return Err(e),
-                    }
-                },
-            }
+                        return Err(e);
+                    },
+                }
+            },
         }
     }
 }
 
-impl<T> Sink for FramedUnbuffered<T>
-where
-    T: AsyncWrite,
-{
-    type SinkItem = Bytes;
-    type SinkError = io::Error;
+fn start_send_frame(
+    config: &FramedUnbufferedBuilder,
+    state: &mut WriteState,
+    data_buffer: Bytes,
+) -> io::Result<AsyncSink<Bytes>> {
+    let write_state = mem::replace(state, WriteState::Invalid);
+    match write_state {
+        WriteState::Invalid => unreachable!(),
+        WriteState::WaitingForInput => {
+            let (size_buffer, size_len) = match config.encode_header(data_buffer.len()) {
+                Ok(header) => header,
+                Err(e) => {
+                    *state = WriteState::WaitingForInput;
+                    return Err(e);
+                },
+            };
+            *state = WriteState::WritingSize {
+                bytes_written: 0,
+                size_buffer,
+                size_len,
+                data_buffer,
+            };
+            Ok(AsyncSink::Ready)
+        },
+        WriteState::WritingSize { .. } | WriteState::WritingData { .. } => {
+            *state = write_state;
+            Ok(AsyncSink::NotReady(data_buffer))
+        },
+    }
+}
 
-    fn start_send(&mut self, data_buffer: Bytes) -> io::Result<AsyncSink<Bytes>> {
-        let write_state = mem::replace(&mut self.write_state, WriteState::Invalid);
+fn poll_write<S: AsyncWrite>(
+    stream: &mut S,
+    state: &mut WriteState,
+) -> io::Result<Async<()>> {
+    loop {
+        let write_state = mem::replace(state, WriteState::Invalid);
         match write_state {
             WriteState::Invalid => unreachable!(),
             WriteState::WaitingForInput => {
-                let len = data_buffer.len() as u32;
-                let size_buffer = [
-                    (len >> 24) as u8,
-                    ((len >> 16) & 0xff) as u8,
-                    ((len >> 8) & 0xff) as u8,
-                    (len & 0xff) as u8,
-                ];
-                self.write_state = WriteState::WritingSize {
-                    bytes_written: 0,
-                    size_buffer,
-                    data_buffer,
-                };
-                return Ok(AsyncSink::Ready);
+                *state = WriteState::WaitingForInput;
+                return Ok(Async::Ready(()));
+            },
+            WriteState::WritingSize { size_buffer, size_len, data_buffer, mut bytes_written } => {
+                let range = (bytes_written as usize)..(size_len as usize);
+                match stream.write(&size_buffer[range]) {
+                    Ok(0) => {
+                        *state = WriteState::WritingSize {
+                            size_buffer, size_len, data_buffer, bytes_written,
+                        };
+                        return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+                    },
+                    Ok(n) => {
+                        bytes_written += n as u8;
+                        if bytes_written == size_len {
+                            *state = WriteState::WritingData {
+                                data_buffer,
+                                bytes_written: 0,
+                            };
+                        } else {
+                            *state = WriteState::WritingSize {
+                                size_buffer, size_len, data_buffer, bytes_written,
+                            }
+                        }
+                    },
+                    Err(e) => {
+                        *state = WriteState::WritingSize {
+                            size_buffer, size_len, data_buffer, bytes_written,
+                        };
+                        if e.kind() == io::ErrorKind::WouldBlock {
+                            return Ok(Async::NotReady);
+                        }
+                        return Err(e);
+                    },
+                }
             },
-            WriteState::WritingSize { .. } | WriteState::WritingData { .. } => {
-                self.write_state = write_state;
-                return Ok(AsyncSink::NotReady(data_buffer));
+            WriteState::WritingData { data_buffer, mut bytes_written } => {
+                if bytes_written == data_buffer.len() {
+                    *state = WriteState::WaitingForInput;
+                    continue;
+                }
+                match stream.write(&data_buffer[bytes_written..]) {
+                    Ok(0) => {
+                        *state = WriteState::WritingData {
+                            data_buffer, bytes_written,
+                        };
+                        return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+                    },
+                    Ok(n) => {
+                        bytes_written += n;
+                        *state = WriteState::WritingData {
+                            data_buffer, bytes_written,
+                        };
+                    },
+                    Err(e) => {
+                        *state = WriteState::WritingData {
+                            data_buffer, bytes_written,
+                        };
+                        if e.kind() == io::ErrorKind::WouldBlock {
+                            return Ok(Async::NotReady);
+                        }
+                        return Err(e);
+                    },
+                }
             },
         }
     }
+}
 
-    fn poll_complete(&mut self) -> io::Result<Async<()>> {
-        loop {
-            let write_state = mem::replace(&mut self.write_state, WriteState::Invalid);
-            match write_state {
-                WriteState::Invalid => unreachable!(),
-                WriteState::WaitingForInput => {
-                    self.write_state = WriteState::WaitingForInput;
-                    return Ok(Async::Ready(()));
-                },
-                WriteState::WritingSize { size_buffer, data_buffer, mut bytes_written } => {
-                    match self.stream.write(&size_buffer[(bytes_written as usize)..]) {
-                        Ok(n) => {
-                            if n == 0 {
-                                return Err(io::Error::from(io::ErrorKind::BrokenPipe));
-                            }
-                            bytes_written += n as u8;
-                            if bytes_written == 4 {
-                                self.write_state = WriteState::WritingData {
-                                    data_buffer,
-                                    bytes_written: 0,
-                                };
-                            } else {
-                                self.write_state = WriteState::WritingSize {
-                                    size_buffer, data_buffer, bytes_written,
-                                }
-                            }
-                        },
-                        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                            self.write_state = WriteState::WritingSize {
-                                size_buffer, data_buffer, bytes_written,
-                            };
-                            return Ok(Async::NotReady);
-                        },
-                        Err(e) => return Err(e),
-                    }
-                },
-                WriteState::WritingData { data_buffer, mut bytes_written } => {
-                    match self.stream.write(&data_buffer[(bytes_written as usize)..]) {
-                        Ok(n) => {
-                            if n == 0 {
-                                return Err(io::Error::from(io::ErrorKind::BrokenPipe));
-                            }
-                            bytes_written += n as u32;
-                            if bytes_written == data_buffer.len() as u32 {
-                                self.write_state = WriteState::WaitingForInput;
-                            } else {
-                                self.write_state = WriteState::WritingData {
-                                    data_buffer, bytes_written,
-                                }
-                            }
-                        },
-                        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                            self.write_state = WriteState::WritingData {
-                                data_buffer, bytes_written,
-                            };
-                            return Ok(Async::NotReady);
-                        },
-                        Err(e) => return Err(e),
-                    }
-                },
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{Future, Sink};
+    use std::io::Cursor;
+
+    fn round_trip(builder: FramedUnbufferedBuilder, frames: &[&[u8]]) -> Vec<u8> {
+        let mut framed = builder.build(Cursor::new(Vec::new()));
+        for frame in frames {
+            let res = unwrap!(framed.start_send(Bytes::from(*frame)));
+            assert!(res.is_ready());
+            assert!(unwrap!(framed.poll_complete()).is_ready());
+        }
+        let encoded = unwrap!(framed.into_inner()).into_inner();
+
+        let mut framed = builder.build(Cursor::new(encoded.clone()));
+        for frame in frames {
+            match unwrap!(framed.poll()) {
+                Async::Ready(Some(data)) => assert_eq!(&data[..], *frame),
+                _ => panic!("expected a frame"),
             }
         }
+        match unwrap!(framed.poll()) {
+            Async::Ready(None) => (),
+            _ => panic!("expected the stream to end"),
+        }
+        encoded
     }
-}
 
+    #[test]
+    fn prefix_formats() {
+        let frames: &[&[u8]] = &[b"hello", b"", &[7u8; 300]];
+
+        let encoded = round_trip(FramedUnbuffered::builder(), frames);
+        assert_eq!(&encoded[..4], &[0, 0, 0, 5]);
+
+        let builder = FramedUnbuffered::builder().prefix_width(2).little_endian();
+        let encoded = round_trip(builder, frames);
+        assert_eq!(&encoded[..2], &[5, 0]);
+
+        let builder = FramedUnbuffered::builder().prefix_width(3).length_includes_header(true);
+        let encoded = round_trip(builder, frames);
+        assert_eq!(&encoded[..3], &[0, 0, 8]);
+
+        let encoded = round_trip(FramedUnbuffered::builder().varint(), frames);
+        assert_eq!(&encoded[7..9], &[0xac, 0x02]);
+
+        let builder = FramedUnbuffered::builder().varint().length_includes_header(true);
+        let encoded = round_trip(builder, &[&[0u8; 127]]);
+        assert_eq!(&encoded[..2], &[0x81, 0x01]);
+    }
+
+    #[test]
+    fn frame_too_long_for_prefix() {
+        let mut framed = FramedUnbuffered::builder().prefix_width(1).build(Cursor::new(Vec::new()));
+        match framed.start_send(Bytes::from(&[0u8; 256][..])) {
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => (),
+            _ => panic!("expected an error"),
+        }
+        assert!(unwrap!(framed.start_send(Bytes::from(&[0u8; 255][..]))).is_ready());
+    }
+
+    #[test]
+    fn max_frame_length() {
+        let builder = FramedUnbuffered::builder().max_frame_length(4);
+
+        let mut framed = builder.build(Cursor::new(Vec::new()));
+        match framed.start_send(Bytes::from(&b"hello"[..])) {
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidInput => (),
+            _ => panic!("expected an error"),
+        }
+
+        let mut framed = builder.build(Cursor::new(vec![0xff, 0xff, 0xff, 0xff]));
+        match framed.poll() {
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidData => (),
+            _ => panic!("expected an error"),
+        }
+
+        let header = unwrap!(FramedUnbufferedBuilder::default().decode_header(&[0xff; 4]));
+        assert_eq!(header, Some(0xffff_ffff));
+    }
+
+    #[test]
+    fn invalid_header_is_kept() {
+        let builder = FramedUnbuffered::builder().max_frame_length(4);
+        let mut framed = builder.build(Cursor::new(vec![0, 0, 0, 9]));
+        for _ in 0..2 {
+            match framed.poll() {
+                Err(ref e) if e.kind() == io::ErrorKind::InvalidData => (),
+                _ => panic!("expected an error"),
+            }
+        }
+        assert_eq!(&framed.into_parts().read_buffer[..], &[0, 0, 0, 9]);
+
+        let mut framed = FramedUnbuffered::builder().varint().build(Cursor::new(vec![0xff; 10]));
+        for _ in 0..2 {
+            match framed.poll() {
+                Err(ref e) if e.kind() == io::ErrorKind::InvalidData => (),
+                _ => panic!("expected an error"),
+            }
+        }
+        assert_eq!(&framed.into_parts().read_buffer[..], &[0xff; 10][..]);
+    }
+
+    #[test]
+    fn split_and_reunite() {
+        let framed = FramedUnbuffered::new(Cursor::new(Vec::new()));
+        let (read_half, write_half) = framed.split();
+        let write_half = unwrap!(write_half.send(Bytes::from(&b"hello"[..])).wait());
+        let framed = unwrap!(read_half.reunite(write_half));
+        let encoded = unwrap!(framed.into_inner()).into_inner();
+        assert_eq!(&encoded[..], b"\x00\x00\x00\x05hello");
+
+        let (read_half, _) = FramedUnbuffered::new(Cursor::new(Vec::<u8>::new())).split();
+        let (_, write_half) = FramedUnbuffered::new(Cursor::new(Vec::<u8>::new())).split();
+        assert!(read_half.reunite(write_half).is_err());
+    }
+
+    #[test]
+    fn into_parts_and_back() {
+        let builder = FramedUnbuffered::builder().varint();
+        let encoded = round_trip(builder, &[b"hello"]);
+
+        let mut framed = builder.build(Cursor::new(encoded[..3].to_vec()));
+        match framed.poll() {
+            Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => (),
+            _ => panic!("expected an error"),
+        }
+        let mut parts = framed.into_parts();
+        assert_eq!(&parts.read_buffer[..], &encoded[..3]);
+        assert!(parts.write_buffer.is_empty());
+
+        parts.stream = Cursor::new(encoded[3..].to_vec());
+        let mut framed = unwrap!(builder.build_from_parts(parts));
+        match unwrap!(framed.poll()) {
+            Async::Ready(Some(data)) => assert_eq!(&data[..], b"hello"),
+            _ => panic!("expected a frame"),
+        }
+
+        let mut buffer = [0u8; 3];
+        let mut framed = FramedUnbuffered::new(Cursor::new(&mut buffer[..]));
+        assert!(unwrap!(framed.start_send(Bytes::from(&b"hello"[..]))).is_ready());
+        assert!(framed.poll_complete().is_err());
+        let parts = framed.into_parts();
+        assert_eq!(&parts.write_buffer[..], b"\x05hello");
+    }
+
+    #[test]
+    fn from_parts_returns_parts_on_error() {
+        let parts = FramedUnbufferedParts {
+            stream: Cursor::new(b"rest".to_vec()),
+            read_buffer: BytesMut::from(&b"\x00\x00\x00\x01a\x00"[..]),
+            write_buffer: Bytes::from(&b"unsent"[..]),
+        };
+        let (e, parts) = match FramedUnbuffered::from_parts(parts) {
+            Err(err) => err,
+            Ok(..) => panic!("expected an error"),
+        };
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(&parts.stream.into_inner()[..], b"rest");
+        assert_eq!(&parts.read_buffer[..], b"\x00\x00\x00\x01a\x00");
+        assert_eq!(&parts.write_buffer[..], b"unsent");
+    }
+}
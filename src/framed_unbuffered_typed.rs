@@ -0,0 +1,295 @@
+use bytes::{Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncWrite};
+use futures::{Stream, Sink, Async, AsyncSink};
+use std::io;
+use framed_unbuffered::FramedUnbuffered;
+
+#[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+use std::marker::PhantomData;
+#[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+use serde::Serialize;
+#[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+use serde::de::DeserializeOwned;
+
+/// Converts messages to and from the frames sent over a `FramedUnbufferedTyped`.
+pub trait Codec {
+    /// The type of message sent and received.
+    type Item;
+    /// The error returned when a message can't be encoded or decoded. Errors from the underlying
+    /// stream are converted into this type.
+    type Error: From<io::Error>;
+
+    /// Encode a message into a frame.
+    fn encode(&mut self, item: Self::Item) -> Result<Bytes, Self::Error>;
+
+    /// Decode a frame into a message.
+    fn decode(&mut self, data: BytesMut) -> Result<Self::Item, Self::Error>;
+}
+
+/// Wraps a `FramedUnbuffered` and uses a `Codec` to send and receive messages rather than raw
+/// frames.
+pub struct FramedUnbufferedTyped<T, C> {
+    framed: FramedUnbuffered<T>,
+    codec: C,
+}
+
+impl<T, C> FramedUnbufferedTyped<T, C> {
+    pub fn new(stream: T, codec: C) -> FramedUnbufferedTyped<T, C> {
+        FramedUnbufferedTyped::from_framed(FramedUnbuffered::new(stream), codec)
+    }
+
+    /// Create a `FramedUnbufferedTyped` from an existing `FramedUnbuffered`. This can be used to
+    /// send messages using a non-default length-prefix format.
+    pub fn from_framed(framed: FramedUnbuffered<T>, codec: C) -> FramedUnbufferedTyped<T, C> {
+        FramedUnbufferedTyped {
+            framed,
+            codec,
+        }
+    }
+
+    /// Unwrap the underlying stream. Returns `None` if a frame is partially read or written. See
+    /// `FramedUnbuffered::into_inner`.
+    pub fn into_inner(self) -> Option<T> {
+        self.framed.into_inner()
+    }
+
+    /// Unwrap the underlying `FramedUnbuffered`, discarding the codec.
+    pub fn into_framed(self) -> FramedUnbuffered<T> {
+        self.framed
+    }
+}
+
+impl<T, C> Stream for FramedUnbufferedTyped<T, C>
+where
+    T: AsyncRead,
+    C: Codec,
+{
+    type Item = C::Item;
+    type Error = C::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<C::Item>>, C::Error> {
+        match try_ready!(self.framed.poll()) {
+            Some(data) => Ok(Async::Ready(Some(self.codec.decode(data)?))),
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+impl<T, C> Sink for FramedUnbufferedTyped<T, C>
+where
+    T: AsyncWrite,
+    C: Codec,
+{
+    type SinkItem = C::Item;
+    type SinkError = C::Error;
+
+    fn start_send(&mut self, item: C::Item) -> Result<AsyncSink<C::Item>, C::Error> {
+        // Only encode the message once the previous frame has been written, otherwise we'd have
+        // no way to hand the message back.
+        if let Async::NotReady = self.framed.poll_complete()? {
+            return Ok(AsyncSink::NotReady(item));
+        }
+        let data = self.codec.encode(item)?;
+        match self.framed.start_send(data)? {
+            AsyncSink::Ready => Ok(AsyncSink::Ready),
+            AsyncSink::NotReady(..) => unreachable!(),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, C::Error> {
+        Ok(self.framed.poll_complete()?)
+    }
+}
+
+/// A `Codec` which encodes messages using bincode. Encoding and decoding errors are returned as
+/// `io::ErrorKind::InvalidData` errors.
+#[cfg(feature = "bincode-codec")]
+pub struct BincodeCodec<M> {
+    _ph: PhantomData<fn(M) -> M>,
+}
+
+#[cfg(feature = "bincode-codec")]
+impl<M> BincodeCodec<M> {
+    pub fn new() -> BincodeCodec<M> {
+        BincodeCodec {
+            _ph: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "bincode-codec")]
+impl<M> Default for BincodeCodec<M> {
+    fn default() -> BincodeCodec<M> {
+        BincodeCodec::new()
+    }
+}
+
+#[cfg(feature = "bincode-codec")]
+impl<M> Codec for BincodeCodec<M>
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Item = M;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: M) -> io::Result<Bytes> {
+        match ::bincode::serialize(&item) {
+            Ok(data) => Ok(Bytes::from(data)),
+            Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+
+    fn decode(&mut self, data: BytesMut) -> io::Result<M> {
+        ::bincode::deserialize(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// A `Codec` which encodes messages as JSON. Encoding and decoding errors are returned as
+/// `io::ErrorKind::InvalidData` errors.
+#[cfg(feature = "json-codec")]
+pub struct JsonCodec<M> {
+    _ph: PhantomData<fn(M) -> M>,
+}
+
+#[cfg(feature = "json-codec")]
+impl<M> JsonCodec<M> {
+    pub fn new() -> JsonCodec<M> {
+        JsonCodec {
+            _ph: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "json-codec")]
+impl<M> Default for JsonCodec<M> {
+    fn default() -> JsonCodec<M> {
+        JsonCodec::new()
+    }
+}
+
+#[cfg(feature = "json-codec")]
+impl<M> Codec for JsonCodec<M>
+where
+    M: Serialize + DeserializeOwned,
+{
+    type Item = M;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: M) -> io::Result<Bytes> {
+        match ::serde_json::to_vec(&item) {
+            Ok(data) => Ok(Bytes::from(data)),
+            Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+
+    fn decode(&mut self, data: BytesMut) -> io::Result<M> {
+        ::serde_json::from_slice(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::Future;
+    use std::io::Cursor;
+
+    struct Utf8Codec;
+
+    impl Codec for Utf8Codec {
+        type Item = String;
+        type Error = io::Error;
+
+        fn encode(&mut self, item: String) -> io::Result<Bytes> {
+            Ok(Bytes::from(item))
+        }
+
+        fn decode(&mut self, data: BytesMut) -> io::Result<String> {
+            String::from_utf8(data.to_vec())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let framed = FramedUnbufferedTyped::new(Cursor::new(Vec::new()), Utf8Codec);
+        let framed = unwrap!(framed.send(String::from("hello")).wait());
+        let framed = unwrap!(framed.send(String::from("world")).wait());
+        let encoded = unwrap!(framed.into_inner()).into_inner();
+
+        let framed = FramedUnbufferedTyped::new(Cursor::new(encoded), Utf8Codec);
+        let messages = unwrap!(framed.collect().wait());
+        assert_eq!(messages, vec![String::from("hello"), String::from("world")]);
+    }
+
+    #[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+    #[derive(Debug, PartialEq)]
+    struct Message {
+        id: u32,
+        text: String,
+    }
+
+    #[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+    impl Serialize for Message {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            (self.id, &self.text).serialize(serializer)
+        }
+    }
+
+    #[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+    impl<'de> Deserialize<'de> for Message {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Message, D::Error> {
+            let (id, text) = <(u32, String)>::deserialize(deserializer)?;
+            Ok(Message { id, text })
+        }
+    }
+
+    /// Send some messages through a `FramedUnbufferedTyped` using `codec` and read them back.
+    #[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+    fn codec_round_trip<C>(codec: C)
+    where
+        C: Codec<Item = Message, Error = io::Error> + Default,
+    {
+        let framed = FramedUnbufferedTyped::new(Cursor::new(Vec::new()), codec);
+        let framed = unwrap!(framed.send(Message { id: 1, text: String::from("hello") }).wait());
+        let framed = unwrap!(framed.send(Message { id: 2, text: String::from("world") }).wait());
+        let encoded = unwrap!(framed.into_inner()).into_inner();
+
+        let framed = FramedUnbufferedTyped::new(Cursor::new(encoded), C::default());
+        let received = unwrap!(framed.collect().wait());
+        assert_eq!(received, vec![
+            Message { id: 1, text: String::from("hello") },
+            Message { id: 2, text: String::from("world") },
+        ]);
+    }
+
+    /// Check that a frame which can't be decoded by `codec` fails the stream with `InvalidData`.
+    #[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+    fn codec_rejects_malformed_frame<C>(codec: C)
+    where
+        C: Codec<Item = Message, Error = io::Error>,
+    {
+        let encoded = b"\x00\x00\x00\x03foo".to_vec();
+        let mut framed = FramedUnbufferedTyped::new(Cursor::new(encoded), codec);
+        match framed.poll() {
+            Err(ref e) if e.kind() == io::ErrorKind::InvalidData => (),
+            _ => panic!("expected an error"),
+        }
+    }
+
+    #[cfg(feature = "bincode-codec")]
+    #[test]
+    fn bincode_codec() {
+        codec_round_trip(BincodeCodec::new());
+        codec_rejects_malformed_frame(BincodeCodec::new());
+    }
+
+    #[cfg(feature = "json-codec")]
+    #[test]
+    fn json_codec() {
+        codec_round_trip(JsonCodec::new());
+        codec_rejects_malformed_frame(JsonCodec::new());
+    }
+}
@@ -10,8 +10,11 @@ use until::Until;
 use infallible::Infallible;
 use finally::Finally;
 use with_timeout::WithTimeout;
+use timeout_or_else::TimeoutOrElse;
+use timed_out::TimedOut;
 use first_ok2::FirstOk2;
-use while_driving::WhileDriving;
+use while_driving::{WhileDriving, WhileDrivingFailFast, WhileDrivingEager};
+use drive_set::{DriveSet, WhileDrivingSet};
 use resume_unwind::ResumeUnwind;
 use BoxFuture;
 use BoxSendFuture;
@@ -89,6 +92,24 @@ pub trait FutureExt: Future + Sized {
         WithTimeout::new_at(self, instant)
     }
 
+    /// Runs the future for the given duration. If the timeout expires then the future fails with
+    /// the error returned by `on_timeout`.
+    fn timeout_or_else<G>(self, duration: Duration, on_timeout: G) -> TimeoutOrElse<Self, G>
+    where
+        G: FnOnce(TimedOut) -> Self::Error,
+    {
+        TimeoutOrElse::new(self, duration, on_timeout)
+    }
+
+    /// Runs the future for the given duration. If the timeout expires then the future fails with a
+    /// `TimedOut` error converted into the future's error type.
+    fn timeout_err(self, duration: Duration) -> TimeoutOrElse<Self, fn(TimedOut) -> Self::Error>
+    where
+        Self::Error: From<TimedOut>,
+    {
+        TimeoutOrElse::new(self, duration, From::from)
+    }
+
     /// Run two futures in parallel and yield the value of the first to return success. If both
     /// futures fail, return both errors.
     fn first_ok2<F>(self, other: F) -> FirstOk2<Self, F>
@@ -103,6 +124,24 @@ pub trait FutureExt: Future + Sized {
         WhileDriving::new(self, other)
     }
 
+    /// Resolves `self` while driving `other` in parallel. Fails immediately if `other` fails,
+    /// returning `self` along with the error.
+    fn while_driving_fail_fast<F: Future>(self, other: F) -> WhileDrivingFailFast<Self, F> {
+        WhileDrivingFailFast::new(self, other)
+    }
+
+    /// Resolves `self` while driving `other` in parallel. Finishes as soon as either future
+    /// finishes, returning the other future still running.
+    fn while_driving_eager<F: Future>(self, other: F) -> WhileDrivingEager<Self, F> {
+        WhileDrivingEager::new(self, other)
+    }
+
+    /// Resolves `self` while driving all the futures in `set` in parallel, including any which are
+    /// added to the set while `self` is running.
+    fn while_driving_set<F: Future>(self, set: DriveSet<F>) -> WhileDrivingSet<Self, F> {
+        WhileDrivingSet::new(self, set)
+    }
+
     /// Propogates the result of a `.catch_unwind`, panicking if the future resolves to an `Err`
     fn resume_unwind(self) -> ResumeUnwind<Self>
     where
@@ -0,0 +1,144 @@
+use std::{cmp, mem};
+use std::time::{Duration, Instant};
+use futures::{Async, Future, IntoFuture};
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Runs a future, starting backup attempts if it doesn't finish quickly enough. Created using
+/// `hedge`.
+pub struct Hedge<F, R: IntoFuture> {
+    make_future: F,
+    delay: Duration,
+    max_attempts: usize,
+    attempts: usize,
+    running: Vec<R::Future>,
+    timer: Delay,
+    errors: Vec<R::Error>,
+}
+
+/// Run the future created by `make_future`. If it hasn't finished after `delay` then create and
+/// start another attempt alongside it, and so on until `max_attempts` attempts are running. An
+/// attempt is also started straight away if every running attempt has failed. Yields the value of
+/// the first attempt to succeed and drops the others. If every attempt fails then their errors are
+/// returned in the order they failed.
+///
+/// At least one attempt is always made, even if `max_attempts` is zero.
+pub fn hedge<F, R>(delay: Duration, max_attempts: usize, mut make_future: F) -> Hedge<F, R>
+where
+    F: FnMut() -> R,
+    R: IntoFuture,
+{
+    let future = make_future().into_future();
+    Hedge {
+        make_future,
+        delay,
+        max_attempts: cmp::max(max_attempts, 1),
+        attempts: 1,
+        running: vec![future],
+        timer: Delay::new(Instant::now() + delay),
+        errors: Vec::new(),
+    }
+}
+
+impl<F, R> Future for Hedge<F, R>
+where
+    F: FnMut() -> R,
+    R: IntoFuture,
+{
+    type Item = R::Item;
+    type Error = Vec<R::Error>;
+
+    fn poll(&mut self) -> Result<Async<R::Item>, Vec<R::Error>> {
+        loop {
+            let mut i = 0;
+            while i < self.running.len() {
+                match self.running[i].poll() {
+                    Ok(Async::Ready(x)) => {
+                        self.running.clear();
+                        self.errors.clear();
+                        return Ok(Async::Ready(x));
+                    },
+                    Ok(Async::NotReady) => i += 1,
+                    Err(e) => {
+                        let _ = self.running.swap_remove(i);
+                        self.errors.push(e);
+                    },
+                }
+            }
+
+            if self.attempts == self.max_attempts {
+                if self.running.is_empty() {
+                    return Err(mem::take(&mut self.errors));
+                }
+                return Ok(Async::NotReady);
+            }
+
+            if !self.running.is_empty() {
+                if let Async::NotReady = self.timer.poll().void_unwrap() {
+                    return Ok(Async::NotReady);
+                }
+            }
+
+            self.running.push((self.make_future)().into_future());
+            self.attempts += 1;
+            self.timer.reset(Instant::now() + self.delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::future;
+    use tokio;
+    use FutureExt;
+    use BoxFuture;
+
+    #[test]
+    fn backup_started_after_delay() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let delay = Duration::from_millis(20);
+        let started = Instant::now();
+        let mut attempts = 0;
+        let res = runtime.block_on(hedge(delay, 3, || -> BoxFuture<u32, ()> {
+            attempts += 1;
+            match attempts {
+                1 => future::empty().into_boxed(),
+                n => future::ok(n).into_boxed(),
+            }
+        }));
+        assert_eq!(res, Ok(2));
+        assert!(started.elapsed() >= delay);
+    }
+
+    #[test]
+    fn restarts_immediately_after_failure() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Instant::now();
+        let mut attempts = 0;
+        let res = runtime.block_on(hedge(Duration::from_secs(10), 3, || {
+            attempts += 1;
+            Err::<(), _>(attempts)
+        }));
+        assert_eq!(res, Err(vec![1, 2, 3]));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn errors_in_order_of_failure() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let mut attempts = 0;
+        let res = runtime.block_on(hedge(Duration::from_millis(10), 2, || -> BoxFuture<(), u32> {
+            attempts += 1;
+            match attempts {
+                1 => {
+                    Delay::new(Instant::now() + Duration::from_millis(50))
+                    .then(|_| Err(1))
+                    .into_boxed()
+                },
+                n => future::err(n).into_boxed(),
+            }
+        }));
+        assert_eq!(res, Err(vec![2, 1]));
+    }
+}
@@ -0,0 +1,244 @@
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Poll};
+use tokio::io::{AsyncRead, AsyncWrite};
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Wraps an I/O object and fails reads and writes with `io::ErrorKind::TimedOut` once they have
+/// been blocked for longer than a given duration.
+pub struct IoTimeout<T> {
+    inner: T,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    read_delay: Option<Delay>,
+    write_delay: Option<Delay>,
+}
+
+impl<T> IoTimeout<T> {
+    /// Wrap `inner`, timing out reads and writes which are blocked for longer than `duration`.
+    pub fn new(inner: T, duration: Duration) -> IoTimeout<T> {
+        IoTimeout {
+            inner,
+            read_timeout: Some(duration),
+            write_timeout: Some(duration),
+            read_delay: None,
+            write_delay: None,
+        }
+    }
+
+    /// Set how long reads can be blocked for before timing out. `None` disables the timeout.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+        self.read_timeout = timeout;
+        self.read_delay = None;
+    }
+
+    /// Set how long writes can be blocked for before timing out. `None` disables the timeout.
+    pub fn set_write_timeout(&mut self, timeout: Option<Duration>) {
+        self.write_timeout = timeout;
+        self.write_delay = None;
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unpack the `IoTimeout`, returning the inner I/O object.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+/// Called when an operation would block. Starts the timer if it isn't already running and returns
+/// a `TimedOut` error if it has expired, otherwise `WouldBlock`.
+fn blocked(timeout: Option<Duration>, delay: &mut Option<Delay>, msg: &str) -> io::Error {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return io::Error::from(io::ErrorKind::WouldBlock),
+    };
+    let expired = {
+        delay
+        .get_or_insert_with(|| Delay::new(Instant::now() + timeout))
+        .poll()
+        .void_unwrap()
+        .is_ready()
+    };
+    if expired {
+        *delay = None;
+        return io::Error::new(io::ErrorKind::TimedOut, msg);
+    }
+    io::Error::from(io::ErrorKind::WouldBlock)
+}
+
+impl<T: Read> Read for IoTimeout<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Err(blocked(self.read_timeout, &mut self.read_delay, "read timed out"))
+            },
+            res => {
+                self.read_delay = None;
+                res
+            },
+        }
+    }
+}
+
+impl<T: Write> Write for IoTimeout<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.inner.write(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Err(blocked(self.write_timeout, &mut self.write_delay, "write timed out"))
+            },
+            res => {
+                self.write_delay = None;
+                res
+            },
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.inner.flush() {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                Err(blocked(self.write_timeout, &mut self.write_delay, "flush timed out"))
+            },
+            res => {
+                self.write_delay = None;
+                res
+            },
+        }
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for IoTimeout<T> {}
+
+impl<T: AsyncWrite> AsyncWrite for IoTimeout<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self.inner.shutdown() {
+            Ok(Async::NotReady) => {
+                let e = blocked(self.write_timeout, &mut self.write_delay, "shutdown timed out");
+                if e.kind() == io::ErrorKind::WouldBlock {
+                    return Ok(Async::NotReady);
+                }
+                Err(e)
+            },
+            res => {
+                self.write_delay = None;
+                res
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+    use tokio;
+    use FutureExt;
+
+    struct NeverReady;
+
+    impl Read for NeverReady {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::WouldBlock))
+        }
+    }
+
+    impl AsyncRead for NeverReady {}
+
+    impl Write for NeverReady {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::WouldBlock))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::WouldBlock))
+        }
+    }
+
+    impl AsyncWrite for NeverReady {
+        fn shutdown(&mut self) -> Poll<(), io::Error> {
+            Ok(Async::NotReady)
+        }
+    }
+
+    /// Yields a single byte as each of its delays expires, then EOF.
+    struct Trickle {
+        delays: VecDeque<Delay>,
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let ready = match self.delays.front_mut() {
+                Some(delay) => delay.poll().void_unwrap().is_ready(),
+                None => return Ok(0),
+            };
+            if !ready {
+                return Err(io::Error::from(io::ErrorKind::WouldBlock));
+            }
+            let _ = self.delays.pop_front();
+            buf[0] = 0;
+            Ok(1)
+        }
+    }
+
+    impl AsyncRead for Trickle {}
+
+    fn assert_timed_out<T>(res: io::Result<T>) {
+        match res {
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::TimedOut),
+            Ok(..) => panic!("operation should have timed out"),
+        }
+    }
+
+    #[test]
+    fn blocked_read_times_out() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let io = IoTimeout::new(NeverReady, Duration::from_millis(20));
+        assert_timed_out(runtime.block_on(tokio::io::read(io, vec![0u8; 4])));
+    }
+
+    #[test]
+    fn blocked_write_flush_and_shutdown_time_out() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let duration = Duration::from_millis(20);
+
+        let io = IoTimeout::new(NeverReady, duration);
+        assert_timed_out(runtime.block_on(tokio::io::write_all(io, vec![0u8; 4])));
+
+        let io = IoTimeout::new(NeverReady, duration);
+        assert_timed_out(runtime.block_on(tokio::io::flush(io)));
+
+        let io = IoTimeout::new(NeverReady, duration);
+        assert_timed_out(runtime.block_on(tokio::io::shutdown(io)));
+    }
+
+    #[test]
+    fn successful_read_resets_timer() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Instant::now();
+        let delays = {
+            [40, 80, 120]
+            .iter()
+            .map(|ms| Delay::new(started + Duration::from_millis(*ms)))
+            .collect()
+        };
+        let io = IoTimeout::new(Trickle { delays }, Duration::from_millis(60));
+        let (_, data) = unwrap!(runtime.block_on(tokio::io::read_to_end(io, Vec::new())));
+        assert_eq!(data.len(), 3);
+    }
+
+    #[test]
+    fn disabled_read_timeout() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let mut io = IoTimeout::new(NeverReady, Duration::from_millis(10));
+        io.set_read_timeout(None);
+        let read = tokio::io::read(io, vec![0u8; 4]).with_timeout(Duration::from_millis(40));
+        assert!(unwrap!(runtime.block_on(read)).is_none());
+    }
+}
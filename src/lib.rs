@@ -7,6 +7,13 @@ extern crate log;
 #[macro_use]
 extern crate unwrap;
 extern crate bytes;
+extern crate rand;
+#[cfg(any(feature = "bincode-codec", feature = "json-codec"))]
+extern crate serde;
+#[cfg(feature = "bincode-codec")]
+extern crate bincode;
+#[cfg(feature = "json-codec")]
+extern crate serde_json;
 
 use std::io;
 use futures::{Future, Stream, Sink};
@@ -15,6 +22,7 @@ mod drop_notify;
 mod until;
 mod future_ext;
 mod stream_ext;
+mod sink_ext;
 mod first_ok;
 mod log_errors;
 mod log_error;
@@ -24,6 +32,9 @@ mod finally;
 mod delay;
 mod with_timeout;
 mod thread_future;
+mod thread_pool;
+mod thread_stream;
+mod thread_sink;
 mod first_ok2;
 mod while_driving;
 mod resume_unwind;
@@ -31,25 +42,76 @@ mod with_readiness_timeout;
 pub mod bi_channel;
 pub mod mpsc;
 mod framed_unbuffered;
+mod framed_unbuffered_typed;
+mod retry;
+mod timed_out;
+mod timeout_or_else;
+mod hedge;
+mod first_ok_all;
+mod first_ok_staggered;
+mod quorum;
+mod drive_set;
+mod batched;
+mod chunks_timeout;
+mod throttle;
+mod debounce;
+mod sample;
+mod io_timeout;
+#[cfg(test)]
+mod test_util;
 
 pub use drop_notify::{drop_notify, DropNotify, DropNotice};
 pub use until::Until;
 pub use first_ok::FirstOk;
+pub use first_ok_staggered::FirstOkStaggered;
 pub use log_errors::LogErrors;
 pub use log_error::LogError;
 pub use future_ext::FutureExt;
 pub use stream_ext::StreamExt;
+pub use sink_ext::SinkExt;
+pub use batched::Batched;
+pub use chunks_timeout::ChunksTimeout;
+pub use throttle::Throttle;
+pub use debounce::Debounce;
+pub use sample::Sample;
 pub use infallible::Infallible;
 pub use next_or_else::NextOrElse;
 pub use finally::Finally;
 pub use with_timeout::WithTimeout;
+pub use timed_out::TimedOut;
+pub use timeout_or_else::TimeoutOrElse;
 pub use delay::Delay;
-pub use with_readiness_timeout::WithReadinessTimeout;
-pub use thread_future::{thread_future, ThreadFuture};
+pub use with_readiness_timeout::{WithReadinessTimeout, WithReadinessTimeoutErr, WithHeartbeat};
+pub use thread_future::{
+    thread_future, thread_future_cancellable, thread_future_catch_panic, ThreadFuture, CancelToken,
+    CatchPanic,
+};
+pub use thread_pool::ThreadPool;
+pub use thread_stream::{thread_stream, ThreadStream};
+pub use thread_sink::{thread_sink, ThreadSink};
 pub use first_ok2::FirstOk2;
-pub use while_driving::{WhileDriving, Finish, FinishInner};
+pub use first_ok_all::{first_ok_all, FirstOkAll};
+pub use quorum::{quorum, Quorum};
+pub use hedge::{hedge, Hedge};
+pub use while_driving::{
+    WhileDriving, WhileDrivingFailFast, WhileDrivingEager, WhileDrivingItem, WhileDrivingError,
+    Finish, FinishInner,
+};
+pub use drive_set::{drive_set, DriveSet, DriveSetHandle, WhileDrivingSet};
 pub use resume_unwind::ResumeUnwind;
-pub use framed_unbuffered::FramedUnbuffered;
+pub use framed_unbuffered::{
+    FramedUnbuffered, FramedUnbufferedBuilder, FramedUnbufferedReadHalf,
+    FramedUnbufferedWriteHalf, FramedUnbufferedParts, ReuniteError,
+};
+pub use framed_unbuffered_typed::{Codec, FramedUnbufferedTyped};
+#[cfg(feature = "bincode-codec")]
+pub use framed_unbuffered_typed::BincodeCodec;
+#[cfg(feature = "json-codec")]
+pub use framed_unbuffered_typed::JsonCodec;
+pub use io_timeout::IoTimeout;
+pub use retry::{
+    retry, Retry, RetryPolicy, FixedBackoff, ExponentialBackoff, MaxAttempts, MaxElapsed, Jitter,
+};
 
 pub type BoxFuture<T, E> = Box<Future<Item=T, Error=E>>;
 pub type BoxStream<T, E> = Box<Stream<Item=T, Error=E>>;
@@ -1,9 +1,9 @@
 use std::fmt::Display;
-use futures::{Async, Stream};
+use futures::{Async, AsyncSink, Stream, Sink};
 use void::Void;
 use log;
 
-/// Removes the errors from a stream and logs them.
+/// Removes the errors from a stream or sink and logs them.
 pub struct LogErrors<S> {
     stream: S,
     level: log::Level,
@@ -43,3 +43,41 @@ where
     }
 }
 
+impl<S> Sink for LogErrors<S>
+where
+    S: Sink,
+    S::SinkError: Display,
+{
+    type SinkItem = S::SinkItem;
+    type SinkError = Void;
+
+    fn start_send(&mut self, item: S::SinkItem) -> Result<AsyncSink<S::SinkItem>, Void> {
+        match self.stream.start_send(item) {
+            Ok(x) => Ok(x),
+            Err(e) => {
+                log!(self.level, "{}: {}", self.description, e);
+                Ok(AsyncSink::Ready)
+            },
+        }
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, Void> {
+        match self.stream.poll_complete() {
+            Ok(x) => Ok(x),
+            Err(e) => {
+                log!(self.level, "{}: {}", self.description, e);
+                Ok(Async::Ready(()))
+            },
+        }
+    }
+
+    fn close(&mut self) -> Result<Async<()>, Void> {
+        match self.stream.close() {
+            Ok(x) => Ok(x),
+            Err(e) => {
+                log!(self.level, "{}: {}", self.description, e);
+                Ok(Async::Ready(()))
+            },
+        }
+    }
+}
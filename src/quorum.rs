@@ -0,0 +1,110 @@
+use std::mem;
+use futures::{Async, Future, IntoFuture};
+
+/// Runs a collection of futures in parallel until a given number of them succeed. Created using
+/// `quorum`.
+pub struct Quorum<F: Future> {
+    futures: Vec<F>,
+    required: usize,
+    items: Vec<F::Item>,
+    errors: Vec<F::Error>,
+}
+
+/// Run all the given futures in parallel until `required` of them have succeeded, then yield
+/// their values along with the errors of any futures which failed in the meantime. The remaining
+/// futures are dropped. If so many futures fail that `required` successes are no longer possible
+/// then fail immediately with the values and errors collected so far.
+pub fn quorum<I>(required: usize, futures: I) -> Quorum<<I::Item as IntoFuture>::Future>
+where
+    I: IntoIterator,
+    I::Item: IntoFuture,
+{
+    Quorum {
+        futures: futures.into_iter().map(IntoFuture::into_future).collect(),
+        required,
+        items: Vec::new(),
+        errors: Vec::new(),
+    }
+}
+
+impl<F: Future> Future for Quorum<F> {
+    type Item = (Vec<F::Item>, Vec<F::Error>);
+    type Error = (Vec<F::Item>, Vec<F::Error>);
+
+    fn poll(&mut self) -> Result<Async<Self::Item>, Self::Error> {
+        let mut i = 0;
+        while i < self.futures.len() && self.items.len() < self.required {
+            match self.futures[i].poll() {
+                Ok(Async::Ready(x)) => {
+                    self.futures.swap_remove(i);
+                    self.items.push(x);
+                },
+                Ok(Async::NotReady) => i += 1,
+                Err(e) => {
+                    self.futures.swap_remove(i);
+                    self.errors.push(e);
+                },
+            }
+        }
+
+        if self.items.len() >= self.required {
+            self.futures.clear();
+            let items = mem::take(&mut self.items);
+            let errors = mem::take(&mut self.errors);
+            return Ok(Async::Ready((items, errors)));
+        }
+
+        if self.items.len() + self.futures.len() < self.required {
+            self.futures.clear();
+            let items = mem::take(&mut self.items);
+            let errors = mem::take(&mut self.errors);
+            return Err((items, errors));
+        }
+
+        Ok(Async::NotReady)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::future;
+    use FutureExt;
+    use BoxFuture;
+
+    #[test]
+    fn quorum_reached() {
+        let futures: Vec<BoxFuture<u32, u32>> = vec![
+            future::ok(1).into_boxed(),
+            future::empty().into_boxed(),
+            future::err(2).into_boxed(),
+            future::ok(3).into_boxed(),
+        ];
+        let (mut items, errors) = unwrap!(quorum(2, futures).wait());
+        items.sort();
+        assert_eq!(items, vec![1, 3]);
+        assert!(errors.len() <= 1);
+    }
+
+    #[test]
+    fn quorum_fails_early() {
+        let futures: Vec<BoxFuture<u32, u32>> = vec![
+            future::err(1).into_boxed(),
+            future::empty().into_boxed(),
+            future::ok(2).into_boxed(),
+            future::err(3).into_boxed(),
+        ];
+        let (items, mut errors) = quorum(3, futures).wait().unwrap_err();
+        errors.sort();
+        assert_eq!(items, vec![2]);
+        assert_eq!(errors, vec![1, 3]);
+    }
+
+    #[test]
+    fn quorum_of_zero() {
+        let futures: Vec<BoxFuture<u32, u32>> = vec![future::empty().into_boxed()];
+        let (items, errors) = unwrap!(quorum(0, futures).wait());
+        assert!(items.is_empty());
+        assert!(errors.is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+use std::{cmp, mem};
+use std::time::{Duration, Instant};
+use futures::{Async, Future, IntoFuture};
+use rand::{self, Rng};
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Decides whether, and after how long, a failed attempt should be retried.
+pub trait RetryPolicy {
+    /// Called after an attempt fails. `attempt` is the number of attempts made so far, starting
+    /// at one, and `elapsed` is the time since the first attempt was started. Returns the delay to
+    /// wait before starting the next attempt, or `None` to give up.
+    fn next_delay(&mut self, attempt: u32, elapsed: Duration) -> Option<Duration>;
+
+    /// Give up after at most `max` attempts.
+    fn max_attempts(self, max: u32) -> MaxAttempts<Self>
+    where
+        Self: Sized
+    {
+        MaxAttempts {
+            policy: self,
+            max,
+        }
+    }
+
+    /// Give up rather than start an attempt more than `max` after the first attempt started.
+    fn max_elapsed(self, max: Duration) -> MaxElapsed<Self>
+    where
+        Self: Sized
+    {
+        MaxElapsed {
+            policy: self,
+            max,
+        }
+    }
+
+    /// Randomize the delays of this policy, picking a delay uniformly between zero and the delay
+    /// the policy would otherwise have used.
+    fn with_jitter(self) -> Jitter<Self>
+    where
+        Self: Sized
+    {
+        Jitter {
+            policy: self,
+        }
+    }
+}
+
+/// Retry forever, waiting the same delay between every attempt.
+#[derive(Debug, Clone)]
+pub struct FixedBackoff {
+    delay: Duration,
+}
+
+impl FixedBackoff {
+    pub fn new(delay: Duration) -> FixedBackoff {
+        FixedBackoff {
+            delay,
+        }
+    }
+}
+
+impl RetryPolicy for FixedBackoff {
+    fn next_delay(&mut self, _attempt: u32, _elapsed: Duration) -> Option<Duration> {
+        Some(self.delay)
+    }
+}
+
+/// Retry forever, starting with a delay of `initial` and doubling it after every attempt until it
+/// reaches `max`.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    initial: Duration,
+    max: Duration,
+}
+
+impl ExponentialBackoff {
+    pub fn new(initial: Duration, max: Duration) -> ExponentialBackoff {
+        ExponentialBackoff {
+            initial,
+            max,
+        }
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn next_delay(&mut self, attempt: u32, _elapsed: Duration) -> Option<Duration> {
+        let shift = cmp::min(attempt.saturating_sub(1), 31);
+        let delay = match self.initial.checked_mul(1 << shift) {
+            Some(delay) => cmp::min(delay, self.max),
+            None => self.max,
+        };
+        Some(delay)
+    }
+}
+
+/// Wraps a policy and gives up after a maximum number of attempts. Created using
+/// `RetryPolicy::max_attempts`.
+#[derive(Debug, Clone)]
+pub struct MaxAttempts<P> {
+    policy: P,
+    max: u32,
+}
+
+impl<P: RetryPolicy> RetryPolicy for MaxAttempts<P> {
+    fn next_delay(&mut self, attempt: u32, elapsed: Duration) -> Option<Duration> {
+        if attempt >= self.max {
+            return None;
+        }
+        self.policy.next_delay(attempt, elapsed)
+    }
+}
+
+/// Wraps a policy and gives up once a maximum amount of time has passed. Created using
+/// `RetryPolicy::max_elapsed`.
+#[derive(Debug, Clone)]
+pub struct MaxElapsed<P> {
+    policy: P,
+    max: Duration,
+}
+
+impl<P: RetryPolicy> RetryPolicy for MaxElapsed<P> {
+    fn next_delay(&mut self, attempt: u32, elapsed: Duration) -> Option<Duration> {
+        let delay = self.policy.next_delay(attempt, elapsed)?;
+        if elapsed + delay > self.max {
+            return None;
+        }
+        Some(delay)
+    }
+}
+
+/// Wraps a policy and randomizes its delays. Created using `RetryPolicy::with_jitter`.
+#[derive(Debug, Clone)]
+pub struct Jitter<P> {
+    policy: P,
+}
+
+impl<P: RetryPolicy> RetryPolicy for Jitter<P> {
+    fn next_delay(&mut self, attempt: u32, elapsed: Duration) -> Option<Duration> {
+        let delay = self.policy.next_delay(attempt, elapsed)?;
+        let nanos = delay.as_secs()
+            .saturating_mul(1_000_000_000)
+            .saturating_add(u64::from(delay.subsec_nanos()));
+        let nanos = rand::thread_rng().gen_range(0, nanos.saturating_add(1));
+        Some(Duration::from_nanos(nanos))
+    }
+}
+
+/// Repeatedly creates and runs a future until it succeeds or the retry policy gives up. Created
+/// using `retry`.
+pub struct Retry<F, R: IntoFuture, P> {
+    make_future: F,
+    policy: P,
+    state: RetryState<R>,
+    started: Instant,
+    errors: Vec<R::Error>,
+}
+
+enum RetryState<R: IntoFuture> {
+    Running(R::Future),
+    Waiting(Delay),
+}
+
+/// Run the future created by `make_future`, creating and running a new one whenever it fails
+/// according to `policy`. If the policy gives up then the errors of every attempt are returned
+/// in a vector.
+pub fn retry<F, R, P>(policy: P, mut make_future: F) -> Retry<F, R, P>
+where
+    F: FnMut() -> R,
+    R: IntoFuture,
+    P: RetryPolicy,
+{
+    let future = make_future().into_future();
+    Retry {
+        make_future,
+        policy,
+        state: RetryState::Running(future),
+        started: Instant::now(),
+        errors: Vec::new(),
+    }
+}
+
+impl<F, R, P> Future for Retry<F, R, P>
+where
+    F: FnMut() -> R,
+    R: IntoFuture,
+    P: RetryPolicy,
+{
+    type Item = R::Item;
+    type Error = Vec<R::Error>;
+
+    fn poll(&mut self) -> Result<Async<R::Item>, Vec<R::Error>> {
+        loop {
+            let next_state = match self.state {
+                RetryState::Running(ref mut future) => {
+                    match future.poll() {
+                        Ok(Async::Ready(x)) => {
+                            self.errors.clear();
+                            return Ok(Async::Ready(x));
+                        },
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                        Err(e) => {
+                            self.errors.push(e);
+                            let attempt = self.errors.len() as u32;
+                            match self.policy.next_delay(attempt, self.started.elapsed()) {
+                                Some(delay) => {
+                                    RetryState::Waiting(Delay::new(Instant::now() + delay))
+                                },
+                                None => {
+                                    let errors = mem::take(&mut self.errors);
+                                    return Err(errors);
+                                },
+                            }
+                        },
+                    }
+                },
+                RetryState::Waiting(ref mut delay) => {
+                    if let Async::NotReady = delay.poll().void_unwrap() {
+                        return Ok(Async::NotReady);
+                    }
+                    RetryState::Running((self.make_future)().into_future())
+                },
+            };
+            self.state = next_state;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio;
+
+    #[test]
+    fn retry_until_success() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let mut attempts = 0;
+        let res = runtime.block_on(retry(FixedBackoff::new(Duration::from_millis(1)), || {
+            attempts += 1;
+            if attempts < 3 {
+                Err(attempts)
+            } else {
+                Ok(attempts)
+            }
+        }));
+        assert_eq!(unwrap!(res), 3);
+    }
+
+    #[test]
+    fn retry_gives_up() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let policy = {
+            ExponentialBackoff::new(Duration::from_millis(1), Duration::from_millis(4))
+            .with_jitter()
+            .max_attempts(4)
+        };
+        let mut attempts = 0;
+        let res = runtime.block_on(retry(policy, || {
+            attempts += 1;
+            Err::<(), _>(attempts)
+        }));
+        assert_eq!(res, Err(vec![1, 2, 3, 4]));
+    }
+}
@@ -0,0 +1,90 @@
+use std::cmp;
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Stream};
+use futures::stream::Fuse;
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Yields the most recent item from a stream at fixed intervals, dropping any other items. Nothing
+/// is yielded for an interval in which the stream yielded no items. The interval is rounded up to
+/// at least one millisecond, the resolution of the timer. Created using `StreamExt::sample`.
+pub struct Sample<S: Stream> {
+    stream: Fuse<S>,
+    interval: Duration,
+    deadline: Instant,
+    delay: Delay,
+    latest: Option<S::Item>,
+}
+
+impl<S: Stream> Sample<S> {
+    pub fn new(stream: S, interval: Duration) -> Sample<S> {
+        let interval = cmp::max(interval, Duration::from_millis(1));
+        let deadline = Instant::now() + interval;
+        Sample {
+            stream: stream.fuse(),
+            interval,
+            deadline,
+            delay: Delay::new(deadline),
+            latest: None,
+        }
+    }
+}
+
+impl<S: Stream> Stream for Sample<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<S::Item>>, S::Error> {
+        loop {
+            match self.stream.poll()? {
+                Async::Ready(Some(item)) => {
+                    self.latest = Some(item);
+                },
+                Async::Ready(None) => return Ok(Async::Ready(self.latest.take())),
+                Async::NotReady => break,
+            }
+        }
+
+        while let Async::Ready(()) = self.delay.poll().void_unwrap() {
+            self.deadline += self.interval;
+            self.delay.reset(self.deadline);
+            if let Some(item) = self.latest.take() {
+                return Ok(Async::Ready(Some(item)));
+            }
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::stream;
+    use tokio;
+    use test_util::timed_stream;
+    use FutureExt;
+    use StreamExt;
+
+    #[test]
+    fn yields_latest_item_each_tick() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Instant::now();
+        let items = [(0, 1), (10, 2), (70, 3), (80, 4), (200, 5)];
+        let stream = {
+            timed_stream::<_, ()>(started, &items)
+            .sample(Duration::from_millis(50))
+        };
+        assert_eq!(unwrap!(runtime.block_on(stream.collect())), vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn zero_interval_does_not_spin() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let stream = {
+            stream::poll_fn(|| Ok::<Async<Option<u32>>, ()>(Async::NotReady))
+            .sample(Duration::from_secs(0))
+        };
+        let res = runtime.block_on(stream.into_future().with_timeout(Duration::from_millis(20)));
+        assert!(unwrap!(res.map_err(|_| ())).is_none());
+    }
+}
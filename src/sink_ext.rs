@@ -0,0 +1,154 @@
+use std::fmt::Display;
+use std::time::{Instant, Duration};
+use futures::{Future, Sink};
+use log;
+
+use until::Until;
+use log_errors::LogErrors;
+use finally::Finally;
+use with_timeout::WithTimeout;
+use batched::Batched;
+use throttle::Throttle;
+use {BoxSink, BoxSendSink};
+
+/// Extension trait for `Sink`.
+///
+/// Most of these methods share their names with methods of `StreamExt`. On types which are both a
+/// `Stream` and a `Sink`, such as `FramedUnbuffered`, calling them with method syntax is
+/// ambiguous when both traits are in scope, so use `StreamExt::until(x, ..)` or
+/// `SinkExt::until(x, ..)` to pick one.
+pub trait SinkExt: Sink + Sized {
+    /// Wraps a sink into a boxed sink, making type-checking easier at the expense of an extra
+    /// layer of indirection at runtime.
+    fn into_boxed(self) -> BoxSink<Self::SinkItem, Self::SinkError>
+    where
+        Self: 'static
+    {
+        Box::new(self)
+    }
+
+    fn into_send_boxed(self) -> BoxSendSink<Self::SinkItem, Self::SinkError>
+    where
+        Self: Send + 'static,
+    {
+        Box::new(self)
+    }
+
+    /// Run this sink until some condition is met. `condition` is a future which returns `()`,
+    /// after which any items sent to the sink are discarded.
+    fn until<C>(self, condition: C) -> Until<Self, C>
+    where
+        C: Future<Item=()>,
+        Self::SinkError: From<C::Error>
+    {
+        Until::new(self, condition)
+    }
+
+    /// Removes the errors from this sink and log them. `description` is prepended to the log
+    /// messages. Items which fail to send are dropped. The returned sink has error type `Void`
+    /// since the errors have been removed.
+    fn log_errors(self, level: log::Level, description: &'static str) -> LogErrors<Self>
+    where
+        Self::SinkError: Display
+    {
+        LogErrors::new(self, level, description)
+    }
+
+    /// Sends items to the sink and runs the provided callback when the sink is closed. The
+    /// callback will also be run if the entire sink is dropped.
+    fn finally<D>(self, on_drop: D) -> Finally<Self, D>
+    where
+        D: FnOnce()
+    {
+        Finally::new(self, on_drop)
+    }
+
+    /// Runs the sink for the given duration, after which any items sent to the sink are
+    /// discarded.
+    fn with_timeout(self, duration: Duration) -> WithTimeout<Self> {
+        WithTimeout::new(self, duration)
+    }
+
+    /// Runs the sink until the given instant, after which any items sent to the sink are
+    /// discarded.
+    fn with_timeout_at(self, instant: Instant) -> WithTimeout<Self> {
+        WithTimeout::new_at(self, instant)
+    }
+
+    /// Adapts a sink of `Vec`s into a sink of individual items by collecting items into batches.
+    /// A batch is sent once it contains `max_items` items or once `max_delay` has passed since its
+    /// first item was added.
+    fn batched<T>(self, max_items: usize, max_delay: Duration) -> Batched<Self, T>
+    where
+        Self: Sink<SinkItem=Vec<T>>,
+    {
+        Batched::new(self, max_items, max_delay)
+    }
+
+    /// Limits the rate at which items are sent to the sink to one every `interval`, allowing
+    /// bursts of up to `burst` items after the sink has been idle.
+    fn throttle(self, interval: Duration, burst: u32) -> Throttle<Self> {
+        Throttle::new(self, interval, burst)
+    }
+}
+
+impl<T: Sink + Sized> SinkExt for T {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{future, Stream};
+    use futures::sync::{mpsc, oneshot};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use tokio;
+    use delay::Delay;
+
+    #[test]
+    fn until_discards_after_condition() {
+        let (tx, rx) = mpsc::unbounded::<u32>();
+        let (cond_tx, cond_rx) = oneshot::channel();
+        let sink = tx.sink_map_err(|_| ()).until(cond_rx.map_err(|_| ()));
+        let sink = unwrap!(sink.send(1).wait());
+        unwrap!(cond_tx.send(()));
+        let sink = unwrap!(sink.send(2).wait());
+        drop(sink);
+        assert_eq!(unwrap!(rx.collect().wait()), vec![1]);
+    }
+
+    #[test]
+    fn finally_runs_on_close() {
+        let (tx, _rx) = mpsc::unbounded::<u32>();
+        let closed = Rc::new(Cell::new(false));
+        let mut sink = {
+            let closed = closed.clone();
+            tx.finally(move || closed.set(true))
+        };
+        assert!(unwrap!(sink.start_send(1)).is_ready());
+        assert!(!closed.get());
+        unwrap!(future::poll_fn(|| sink.close()).wait());
+        assert!(closed.get());
+    }
+
+    #[test]
+    fn with_timeout_discards_after_deadline() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let (tx, rx) = mpsc::unbounded::<u32>();
+        let sink = tx.with_timeout(Duration::from_millis(10));
+        let sink = unwrap!(runtime.block_on(sink.send(1)));
+        let delay = Delay::new(Instant::now() + Duration::from_millis(20));
+        unwrap!(runtime.block_on(delay));
+        let sink = unwrap!(runtime.block_on(sink.send(2)));
+        drop(sink);
+        assert_eq!(unwrap!(runtime.block_on(rx.collect())), vec![1]);
+    }
+
+    #[test]
+    fn log_errors_drops_failed_items() {
+        let (tx, rx) = mpsc::unbounded::<u32>();
+        drop(rx);
+        let sink = tx.log_errors(log::Level::Debug, "send failed");
+        let sink = unwrap!(sink.send(1).wait());
+        unwrap!(sink.send(2).wait());
+    }
+}
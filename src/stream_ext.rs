@@ -1,17 +1,24 @@
 use std::fmt::Display;
 use std::time::{Instant, Duration};
-use futures::{Future, Stream};
+use futures::{Future, IntoFuture, Stream};
 use log;
 use void::Void;
 
 use until::Until;
 use first_ok::FirstOk;
+use first_ok_staggered::FirstOkStaggered;
 use log_errors::LogErrors;
 use infallible::Infallible;
 use next_or_else::NextOrElse;
 use finally::Finally;
 use with_timeout::WithTimeout;
-use with_readiness_timeout::WithReadinessTimeout;
+use timeout_or_else::TimeoutOrElse;
+use timed_out::TimedOut;
+use with_readiness_timeout::{WithReadinessTimeout, WithReadinessTimeoutErr, WithHeartbeat};
+use chunks_timeout::ChunksTimeout;
+use throttle::Throttle;
+use debounce::Debounce;
+use sample::Sample;
 use {BoxStream, BoxSendStream};
 
 /// Extension trait for `Stream`.
@@ -54,6 +61,18 @@ pub trait StreamExt: Stream + Sized {
         FirstOk::new(self)
     }
 
+    /// Adapts a stream of futures to a future which runs them in parallel and yields the value of
+    /// the first one to succeed. Each future is started `stagger` after the previous one, or
+    /// immediately if a running future fails, with at most `max_in_flight` running at once. If the
+    /// stream ends before any future succeeds then all the errors yielded by the stream and its
+    /// futures are returned in a vector, in the order the stream yielded them.
+    fn first_ok_staggered(self, stagger: Duration, max_in_flight: usize) -> FirstOkStaggered<Self>
+    where
+        Self::Item: IntoFuture<Error = Self::Error>,
+    {
+        FirstOkStaggered::new(self, stagger, max_in_flight)
+    }
+
     /// Removes the errors from this stream and log them. `description` is prepended to the log
     /// messages. The returned stream has error type `Void` since the errors have been removed.
     fn log_errors(self, level: log::Level, description: &'static str) -> LogErrors<Self>
@@ -102,9 +121,72 @@ pub trait StreamExt: Stream + Sized {
         WithTimeout::new_at(self, instant)
     }
 
+    /// Runs the stream for the given duration. If the timeout expires then the stream yields the
+    /// error returned by `on_timeout` and then ends.
+    fn timeout_or_else<G>(self, duration: Duration, on_timeout: G) -> TimeoutOrElse<Self, G>
+    where
+        G: FnOnce(TimedOut) -> Self::Error,
+    {
+        TimeoutOrElse::new(self, duration, on_timeout)
+    }
+
+    /// Runs the stream for the given duration. If the timeout expires then the stream yields a
+    /// `TimedOut` error converted into the stream's error type and then ends.
+    fn timeout_err(self, duration: Duration) -> TimeoutOrElse<Self, fn(TimedOut) -> Self::Error>
+    where
+        Self::Error: From<TimedOut>,
+    {
+        TimeoutOrElse::new(self, duration, From::from)
+    }
+
     fn with_readiness_timeout(self, duration: Duration) -> WithReadinessTimeout<Self> {
         WithReadinessTimeout::new(self, duration)
     }
+
+    /// Yields a `TimedOut` error, converted into the stream's error type, whenever the stream goes
+    /// for `duration` without yielding an item. The stream can continue to be used after the
+    /// error.
+    fn with_readiness_timeout_err(self, duration: Duration) -> WithReadinessTimeoutErr<Self>
+    where
+        Self::Error: From<TimedOut>,
+    {
+        WithReadinessTimeoutErr::new(self, duration)
+    }
+
+    /// Yields the item returned by `heartbeat` whenever the stream goes for `duration` without
+    /// yielding an item.
+    fn with_heartbeat<F>(self, duration: Duration, heartbeat: F) -> WithHeartbeat<Self, F>
+    where
+        F: FnMut() -> Self::Item,
+    {
+        WithHeartbeat::new(self, duration, heartbeat)
+    }
+
+    /// Groups the items of the stream into vectors of at most `max_items` items. Unlike
+    /// `Stream::chunks`, a partial chunk is yielded once `max_delay` has passed since its first
+    /// item arrived.
+    fn chunks_timeout(self, max_items: usize, max_delay: Duration) -> ChunksTimeout<Self> {
+        ChunksTimeout::new(self, max_items, max_delay)
+    }
+
+    /// Limits the rate at which items are taken from the stream to one every `interval`, allowing
+    /// bursts of up to `burst` items after the stream has been idle.
+    fn throttle(self, interval: Duration, burst: u32) -> Throttle<Self> {
+        Throttle::new(self, interval, burst)
+    }
+
+    /// Yields an item only once the stream has gone quiet for `duration` after it, dropping any
+    /// items which are followed by another item within `duration`. If the stream ends then the
+    /// last item is yielded straight away.
+    fn debounce(self, duration: Duration) -> Debounce<Self> {
+        Debounce::new(self, duration)
+    }
+
+    /// Yields the most recent item from the stream once every `interval`, dropping any other
+    /// items. If the stream ends then the last item is yielded straight away.
+    fn sample(self, interval: Duration) -> Sample<Self> {
+        Sample::new(self, interval)
+    }
 }
 
 impl<T: Stream + Sized> StreamExt for T {}
@@ -0,0 +1,22 @@
+use std::time::{Duration, Instant};
+use futures::{stream, Future, Stream};
+use delay::Delay;
+use FutureExt;
+use BoxStream;
+
+/// A stream which yields each `x` in `items` once `ms` milliseconds have passed since `started`.
+pub fn timed_stream<T, E>(started: Instant, items: &[(u64, T)]) -> BoxStream<T, E>
+where
+    T: Clone + 'static,
+    E: 'static,
+{
+    let stream = {
+        stream::iter_ok(items.to_vec())
+        .and_then(move |(ms, x)| {
+            Delay::new(started + Duration::from_millis(ms))
+            .map(move |()| x)
+            .infallible()
+        })
+    };
+    Box::new(stream)
+}
@@ -1,13 +1,35 @@
 use futures::{Future, Async};
 use futures::sync::oneshot;
 use std::{thread, panic};
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use void::Void;
+use drop_notify::{drop_notify, DropNotify, DropNotice};
+use thread_pool::QueuedJob;
 
 /// Wraps a synchronous function into a future by running it in its own thread. Created using
-/// `thread_future`.
+/// `thread_future` or `ThreadPool::spawn`.
 pub struct ThreadFuture<R> {
     join_handle: Option<thread::JoinHandle<()>>,
-    rx: oneshot::Receiver<R>,
+    rx: oneshot::Receiver<thread::Result<R>>,
+    queued_job: Option<QueuedJob>,
+    cancel: Option<Cancel>,
+    join_on_drop: bool,
+}
+
+struct Cancel {
+    cancelled: Arc<AtomicBool>,
+    _drop_notify: DropNotify,
+}
+
+/// Passed to the function run by `thread_future_cancellable`. Allows the function to check
+/// whether its `ThreadFuture` has been dropped. `CancelToken` is also a future which resolves when
+/// the `ThreadFuture` is dropped.
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    drop_notice: DropNotice,
 }
 
 /// Run a synchronous function in a separate thread and return its result as a `Future`.
@@ -22,30 +44,197 @@ where
 {
     let (tx, rx) = oneshot::channel();
     let join_handle = Some(thread::spawn(|| {
-        let x = f();
-        let _ = tx.send(x);
+        let res = panic::catch_unwind(AssertUnwindSafe(f));
+        let _ = tx.send(res);
     }));
     ThreadFuture {
         rx,
         join_handle,
+        queued_job: None,
+        cancel: None,
+        join_on_drop: false,
     }
 }
 
-impl<R> Future for ThreadFuture<R> {
-    type Item = R;
-    type Error = Void;
+/// Like `thread_future`, but if the given function panics then the returned future fails with the
+/// panic payload instead of panicking.
+pub fn thread_future_catch_panic<F, R>(f: F) -> CatchPanic<R>
+where
+    R: Send + 'static,
+    F: FnOnce() -> R + Send + 'static
+{
+    thread_future(f).catch_panic()
+}
+
+/// Like `thread_future`, but passes the function a `CancelToken` which is cancelled when the
+/// returned `ThreadFuture` is dropped. The function can use this to stop working early.
+pub fn thread_future_cancellable<F, R>(f: F) -> ThreadFuture<R>
+where
+    R: Send + 'static,
+    F: FnOnce(CancelToken) -> R + Send + 'static
+{
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (drop_notify, drop_notice) = drop_notify();
+    let token = CancelToken {
+        cancelled: cancelled.clone(),
+        drop_notice,
+    };
+    let mut ret = thread_future(move || f(token));
+    ret.cancel = Some(Cancel {
+        cancelled,
+        _drop_notify: drop_notify,
+    });
+    ret
+}
+
+impl<R> ThreadFuture<R> {
+    /// Create a `ThreadFuture` for a job which is waiting to be pushed onto a thread pool's queue.
+    pub(crate) fn queued(
+        rx: oneshot::Receiver<thread::Result<R>>,
+        queued_job: Option<QueuedJob>,
+    ) -> ThreadFuture<R> {
+        ThreadFuture {
+            rx,
+            join_handle: None,
+            queued_job,
+            cancel: None,
+            join_on_drop: false,
+        }
+    }
+
+    /// Convert this future into one which fails with the panic payload if the function panics,
+    /// rather than panicking itself.
+    pub fn catch_panic(self) -> CatchPanic<R> {
+        CatchPanic {
+            inner: self,
+        }
+    }
+
+    /// Block the dropping thread until the spawned thread has exited when this `ThreadFuture` is
+    /// dropped.
+    pub fn join_on_drop(mut self) -> ThreadFuture<R> {
+        self.join_on_drop = true;
+        self
+    }
+
+    fn poll_thread(&mut self) -> Async<thread::Result<R>> {
+        if let Some(mut queued_job) = self.queued_job.take() {
+            if let Async::NotReady = queued_job.poll_push() {
+                self.queued_job = Some(queued_job);
+                return Async::NotReady;
+            }
+        }
 
-    fn poll(&mut self) -> Result<Async<R>, Void> {
         match self.rx.poll() {
-            Ok(x) => Ok(x),
+            Ok(x) => x,
             Err(oneshot::Canceled) => {
-                // The thread must have died. Get the error.
-                match unwrap!(self.join_handle.take()).join() {
-                    Ok(()) => unreachable!(),
-                    Err(e) => panic::resume_unwind(e),
+                match self.join_handle.take() {
+                    // The thread must have died. Get the error.
+                    Some(join_handle) => match join_handle.join() {
+                        Ok(()) => unreachable!(),
+                        Err(e) => Async::Ready(Err(e)),
+                    },
+                    None => {
+                        let msg = "ThreadPool was dropped before the job could be run";
+                        Async::Ready(Err(Box::new(msg)))
+                    },
                 }
             },
         }
     }
 }
 
+impl<R> Future for ThreadFuture<R> {
+    type Item = R;
+    type Error = Void;
+
+    fn poll(&mut self) -> Result<Async<R>, Void> {
+        match self.poll_thread() {
+            Async::Ready(Ok(x)) => Ok(Async::Ready(x)),
+            Async::Ready(Err(e)) => panic::resume_unwind(e),
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl<R> Drop for ThreadFuture<R> {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel.cancelled.store(true, Ordering::SeqCst);
+        }
+        if self.join_on_drop {
+            if let Some(join_handle) = self.join_handle.take() {
+                let _ = join_handle.join();
+            }
+        }
+    }
+}
+
+/// A `ThreadFuture` which fails with the panic payload if its function panics. The error type is
+/// compatible with `FutureExt::resume_unwind`. Created using `thread_future_catch_panic` or
+/// `ThreadFuture::catch_panic`.
+pub struct CatchPanic<R> {
+    inner: ThreadFuture<R>,
+}
+
+impl<R> Future for CatchPanic<R> {
+    type Item = R;
+    type Error = Box<Any + Send + 'static>;
+
+    fn poll(&mut self) -> Result<Async<R>, Box<Any + Send + 'static>> {
+        match self.inner.poll_thread() {
+            Async::Ready(Ok(x)) => Ok(Async::Ready(x)),
+            Async::Ready(Err(e)) => Err(e),
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl CancelToken {
+    /// Returns `true` if the `ThreadFuture` has been dropped.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Future for CancelToken {
+    type Item = ();
+    type Error = Void;
+
+    fn poll(&mut self) -> Result<Async<()>, Void> {
+        self.drop_notice.poll()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn cancelled_on_drop() {
+        let (started_tx, started_rx) = mpsc::channel();
+        let (tx, rx) = mpsc::channel();
+        let future = thread_future_cancellable(move |mut token| {
+            unwrap!(started_tx.send(token.is_cancelled()));
+            unwrap!((&mut token).wait());
+            unwrap!(tx.send(token.is_cancelled()));
+        });
+        assert!(!unwrap!(started_rx.recv()));
+        drop(future.join_on_drop());
+        assert!(unwrap!(rx.try_recv()));
+    }
+
+    #[test]
+    fn catch_panic_yields_payload() {
+        let res = thread_future_catch_panic(|| -> u32 { panic!("oh no") }).wait();
+        let payload = match res {
+            Ok(..) => panic!("expected the future to fail"),
+            Err(payload) => payload,
+        };
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"oh no"));
+
+        let res = thread_future_catch_panic(|| 123).wait();
+        assert_eq!(unwrap!(res), 123);
+    }
+}
@@ -0,0 +1,253 @@
+use futures::Async;
+use futures::task::{self, Task};
+use futures::sync::oneshot;
+use std::{cmp, io, panic, thread};
+use std::collections::{HashMap, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Condvar, Mutex};
+use thread_future::ThreadFuture;
+
+type Job = Box<FnOnce() + Send>;
+
+/// A fixed-size pool of threads for running synchronous functions. Functions are run using
+/// `spawn`, which returns their result as a `ThreadFuture`.
+///
+/// Dropping the pool stops its threads once they have finished the jobs already in the queue and
+/// any jobs still waiting for room in the queue whose `ThreadFuture`s are still alive. Jobs which
+/// are waiting for room are moved onto the queue in the order they were spawned.
+pub struct ThreadPool {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+    max_queued: usize,
+}
+
+struct State {
+    jobs: VecDeque<Job>,
+    waiting: VecDeque<u64>,
+    blocked: HashMap<u64, Task>,
+    next_id: u64,
+    shutdown: bool,
+}
+
+/// A job which is waiting for room in a `ThreadPool`'s queue. The pool's threads keep running
+/// while any of these exist.
+pub(crate) struct QueuedJob {
+    shared: Arc<Shared>,
+    job: Option<Job>,
+    id: u64,
+}
+
+impl ThreadPool {
+    /// Create a pool of `num_threads` threads named after `name`. At most `max_queued` jobs can be
+    /// waiting for a thread at once, further jobs are held in their `ThreadFuture` until there is
+    /// room. At least one thread is always spawned and at least one job can always be queued.
+    pub fn new(name: &str, num_threads: usize, max_queued: usize) -> io::Result<ThreadPool> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                jobs: VecDeque::new(),
+                waiting: VecDeque::new(),
+                blocked: HashMap::new(),
+                next_id: 0,
+                shutdown: false,
+            }),
+            condvar: Condvar::new(),
+            max_queued: cmp::max(max_queued, 1),
+        });
+        let pool = ThreadPool {
+            shared,
+        };
+        for i in 0..cmp::max(num_threads, 1) {
+            let shared = pool.shared.clone();
+            thread::Builder::new()
+            .name(format!("{}-{}", name, i))
+            .spawn(move || shared.run_worker())?;
+        }
+        Ok(pool)
+    }
+
+    /// Run a synchronous function on the pool and return its result as a `Future`. If the future
+    /// is dropped before the function has started then the function is never run. Jobs which have
+    /// been spawned are still run if the pool is dropped before they start.
+    ///
+    /// # Note
+    ///
+    /// If the given function panics then so will the returned future.
+    pub fn spawn<F, R>(&self, f: F) -> ThreadFuture<R>
+    where
+        R: Send + 'static,
+        F: FnOnce() -> R + Send + 'static
+    {
+        let (tx, rx) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            if tx.is_canceled() {
+                return;
+            }
+            let res = panic::catch_unwind(AssertUnwindSafe(f));
+            let _ = tx.send(res);
+        });
+        let queued_job = {
+            let mut state = unwrap!(self.shared.state.lock());
+            if state.waiting.is_empty() && state.jobs.len() < self.shared.max_queued {
+                state.jobs.push_back(job);
+                self.shared.condvar.notify_one();
+                None
+            } else {
+                let id = state.next_id;
+                state.next_id += 1;
+                state.waiting.push_back(id);
+                Some(QueuedJob {
+                    shared: self.shared.clone(),
+                    job: Some(job),
+                    id,
+                })
+            }
+        };
+        ThreadFuture::queued(rx, queued_job)
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        let mut state = unwrap!(self.shared.state.lock());
+        state.shutdown = true;
+        self.shared.condvar.notify_all();
+    }
+}
+
+impl QueuedJob {
+    /// Try to push the job onto the queue. If the queue is full, or jobs spawned before this one
+    /// are still waiting, then the current task will be notified once it is this job's turn.
+    pub(crate) fn poll_push(&mut self) -> Async<()> {
+        let mut state = unwrap!(self.shared.state.lock());
+        let is_next = state.waiting.front() == Some(&self.id);
+        if !is_next || state.jobs.len() >= self.shared.max_queued {
+            state.blocked.insert(self.id, task::current());
+            return Async::NotReady;
+        }
+        let _ = state.waiting.pop_front();
+        state.blocked.remove(&self.id);
+        state.jobs.push_back(unwrap!(self.job.take()));
+        self.shared.condvar.notify_one();
+        if state.jobs.len() < self.shared.max_queued {
+            state.notify_next();
+        }
+        Async::Ready(())
+    }
+}
+
+impl Drop for QueuedJob {
+    fn drop(&mut self) {
+        if self.job.is_none() {
+            return;
+        }
+        let mut state = unwrap!(self.shared.state.lock());
+        state.blocked.remove(&self.id);
+        if let Some(index) = state.waiting.iter().position(|id| *id == self.id) {
+            let _ = state.waiting.remove(index);
+            if index == 0 {
+                state.notify_next();
+            }
+        }
+        if state.waiting.is_empty() {
+            self.shared.condvar.notify_all();
+        }
+    }
+}
+
+impl State {
+    /// Wake the job at the front of the wait list, if it is blocked.
+    fn notify_next(&mut self) {
+        let task = match self.waiting.front() {
+            Some(id) => self.blocked.remove(id),
+            None => None,
+        };
+        if let Some(task) = task {
+            task.notify();
+        }
+    }
+}
+
+impl Shared {
+    fn run_worker(&self) {
+        loop {
+            let job = {
+                let mut state = unwrap!(self.state.lock());
+                loop {
+                    if let Some(job) = state.jobs.pop_front() {
+                        state.notify_next();
+                        break job;
+                    }
+                    if state.shutdown && state.waiting.is_empty() {
+                        return;
+                    }
+                    state = unwrap!(self.condvar.wait(state));
+                }
+            };
+            job();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{future, Future};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn spawn_more_jobs_than_queue() {
+        let pool = unwrap!(ThreadPool::new("test-pool", 2, 1));
+        let futures: Vec<_> = (0..20u32).map(|i| pool.spawn(move || i * 2)).collect();
+        let res = unwrap!(future::join_all(futures).wait());
+        assert_eq!(res, (0..20u32).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn queued_jobs_run_after_pool_dropped() {
+        let pool = unwrap!(ThreadPool::new("test-pool", 1, 1));
+        let futures: Vec<_> = (0..5u32).map(|i| {
+            pool.spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                i
+            })
+        }).collect();
+        drop(pool);
+        let res = unwrap!(future::join_all(futures).wait());
+        assert_eq!(res, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn waiting_jobs_run_in_spawn_order() {
+        let pool = unwrap!(ThreadPool::new("test-pool", 1, 1));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let spawn = |name| {
+            let order = order.clone();
+            pool.spawn(move || unwrap!(order.lock()).push(name))
+        };
+
+        let a = pool.spawn(move || {
+            unwrap!(started_tx.send(()));
+            let _ = unwrap!(release_rx.lock()).recv();
+        });
+        unwrap!(started_rx.recv());
+        // `b` fills the queue so `c` has to wait for room.
+        let b = spawn("b");
+        let c = spawn("c");
+        unwrap!(release_tx.send(()));
+        unwrap!(a.wait());
+        unwrap!(b.wait());
+
+        // The queue now has room, but `c` hasn't been polled yet so `d` must wait behind it.
+        let d = spawn("d");
+        unwrap!(future::join_all(vec![d, c]).wait());
+        assert_eq!(*unwrap!(order.lock()), vec!["b", "c", "d"]);
+    }
+}
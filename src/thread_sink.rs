@@ -0,0 +1,158 @@
+use futures::{Async, AsyncSink, Sink, Stream};
+use futures::sync::mpsc;
+use futures::task::AtomicTask;
+use std::{thread, panic};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use void::Void;
+
+/// Wraps a blocking function into a sink by running it in its own thread and calling it with every
+/// item sent to the sink. Created using `thread_sink`.
+pub struct ThreadSink<T> {
+    join_handle: Option<thread::JoinHandle<()>>,
+    tx: mpsc::Sender<T>,
+    sent: usize,
+    consumer: Arc<Consumer>,
+}
+
+struct Consumer {
+    consumed: AtomicUsize,
+    exited: AtomicBool,
+    task: AtomicTask,
+}
+
+/// Notifies the sink when the consumer thread exits, including by panicking.
+struct ExitGuard {
+    consumer: Arc<Consumer>,
+}
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        self.consumer.exited.store(true, Ordering::SeqCst);
+        self.consumer.task.notify();
+    }
+}
+
+/// Run `f` in a separate thread and return a `Sink` which passes every item sent to it to `f`.
+/// The sink stops accepting items while `buffer + 1` items are waiting to be consumed, as the
+/// channel reserves a slot for the sink on top of `buffer`. `poll_complete` only resolves once `f`
+/// has been called with every item sent to the sink. The thread exits once the sink is dropped and
+/// the remaining items have been consumed.
+///
+/// # Note
+///
+/// If the given function panics then so will this sink.
+pub fn thread_sink<F, T>(buffer: usize, f: F) -> ThreadSink<T>
+where
+    F: FnMut(T) + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(buffer);
+    let consumer = Arc::new(Consumer {
+        consumed: AtomicUsize::new(0),
+        exited: AtomicBool::new(false),
+        task: AtomicTask::new(),
+    });
+    let join_handle = {
+        let consumer = consumer.clone();
+        Some(thread::spawn(move || {
+            let _exit_guard = ExitGuard {
+                consumer: consumer.clone(),
+            };
+            let mut f = f;
+            for item in rx.wait() {
+                f(unwrap!(item));
+                consumer.consumed.fetch_add(1, Ordering::SeqCst);
+                consumer.task.notify();
+            }
+        }))
+    };
+    ThreadSink {
+        join_handle,
+        tx,
+        sent: 0,
+        consumer,
+    }
+}
+
+impl<T> ThreadSink<T> {
+    fn thread_died(&mut self) -> ! {
+        match unwrap!(self.join_handle.take()).join() {
+            Ok(()) => unreachable!(),
+            Err(e) => panic::resume_unwind(e),
+        }
+    }
+}
+
+impl<T> Sink for ThreadSink<T> {
+    type SinkItem = T;
+    type SinkError = Void;
+
+    fn start_send(&mut self, item: T) -> Result<AsyncSink<T>, Void> {
+        match self.tx.start_send(item) {
+            Ok(AsyncSink::Ready) => {
+                self.sent += 1;
+                Ok(AsyncSink::Ready)
+            },
+            Ok(AsyncSink::NotReady(item)) => Ok(AsyncSink::NotReady(item)),
+            Err(_) => self.thread_died(),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, Void> {
+        match self.tx.poll_complete() {
+            Ok(Async::Ready(())) => (),
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Err(_) => self.thread_died(),
+        }
+
+        self.consumer.task.register();
+        if self.consumer.consumed.load(Ordering::SeqCst) == self.sent {
+            return Ok(Async::Ready(()));
+        }
+        if self.consumer.exited.load(Ordering::SeqCst) {
+            self.thread_died();
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{stream, Future};
+    use std::panic::AssertUnwindSafe;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[test]
+    fn poll_complete_waits_for_consumer() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let seen = seen.clone();
+            thread_sink(1, move |item| {
+                thread::sleep(Duration::from_millis(1));
+                unwrap!(seen.lock()).push(item);
+            })
+        };
+        let (_sink, _) = unwrap!(sink.send_all(stream::iter_ok::<_, Void>(0..20)).wait());
+        assert_eq!(*unwrap!(seen.lock()), (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn consumer_panic_is_propagated() {
+        let sink = thread_sink(1, |item: u32| {
+            if item == 2 {
+                panic!("consumer panicked");
+            }
+        });
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            sink.send_all(stream::iter_ok::<_, Void>(0..5)).wait()
+        }));
+        let payload = match res {
+            Ok(..) => panic!("expected the sink to panic"),
+            Err(payload) => payload,
+        };
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"consumer panicked"));
+    }
+}
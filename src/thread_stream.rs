@@ -0,0 +1,97 @@
+use futures::{Async, Future, Sink, Stream};
+use futures::sync::mpsc;
+use std::{thread, panic};
+use void::Void;
+
+/// Wraps a blocking iterator into a stream by running it in its own thread. Created using
+/// `thread_stream`.
+pub struct ThreadStream<T> {
+    join_handle: Option<thread::JoinHandle<()>>,
+    rx: mpsc::Receiver<T>,
+}
+
+/// Run the iterator returned by `f` in a separate thread and yield its items as a `Stream`. The
+/// thread blocks once `buffer + 1` items are waiting to be taken from the stream, as the channel
+/// reserves a slot for the thread on top of `buffer`. Once the stream is dropped the thread stops
+/// taking items from the iterator.
+///
+/// # Note
+///
+/// If the given function or iterator panics then so will this stream.
+pub fn thread_stream<F, I>(buffer: usize, f: F) -> ThreadStream<I::Item>
+where
+    F: FnOnce() -> I + Send + 'static,
+    I: IntoIterator,
+    I::Item: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(buffer);
+    let join_handle = Some(thread::spawn(move || {
+        let mut tx = tx;
+        for item in f() {
+            tx = match tx.send(item).wait() {
+                Ok(tx) => tx,
+                Err(_) => return,
+            };
+        }
+    }));
+    ThreadStream {
+        rx,
+        join_handle,
+    }
+}
+
+impl<T> Stream for ThreadStream<T> {
+    type Item = T;
+    type Error = Void;
+
+    fn poll(&mut self) -> Result<Async<Option<T>>, Void> {
+        match self.rx.poll() {
+            Ok(Async::Ready(Some(x))) => Ok(Async::Ready(Some(x))),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(None)) | Err(()) => {
+                // The thread has finished. Check whether it panicked.
+                match self.join_handle.take() {
+                    Some(join_handle) => match join_handle.join() {
+                        Ok(()) => Ok(Async::Ready(None)),
+                        Err(e) => panic::resume_unwind(e),
+                    },
+                    None => Ok(Async::Ready(None)),
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc as std_mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn yields_items() {
+        let stream = thread_stream(1, || vec![1, 2, 3]);
+        assert_eq!(unwrap!(stream.collect().wait()), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn producer_stops_when_dropped() {
+        struct SignalOnDrop(std_mpsc::Sender<()>);
+
+        impl Drop for SignalOnDrop {
+            fn drop(&mut self) {
+                let _ = self.0.send(());
+            }
+        }
+
+        let (tx, rx) = std_mpsc::channel();
+        let stream = thread_stream(1, move || {
+            let signal = SignalOnDrop(tx);
+            (0u64..).inspect(move |_| {
+                let _ = &signal;
+            })
+        });
+        assert_eq!(unwrap!(stream.take(3).collect().wait()), vec![0, 1, 2]);
+        unwrap!(rx.recv_timeout(Duration::from_secs(5)));
+    }
+}
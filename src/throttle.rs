@@ -0,0 +1,168 @@
+use std::cmp;
+use std::time::{Duration, Instant};
+use futures::{Async, AsyncSink, Future, Stream, Sink};
+use void::ResultVoidExt;
+use delay::Delay;
+
+/// Limits the rate at which items pass through a stream or sink. Created using
+/// `StreamExt::throttle` or `SinkExt::throttle`.
+///
+/// The rate is limited using a token bucket which holds up to `burst` tokens and gains a token
+/// every `interval`. Each item uses up one token, and items are held back while the bucket is
+/// empty. The bucket starts full.
+pub struct Throttle<T> {
+    inner: T,
+    interval: Duration,
+    burst: u32,
+    tokens: u32,
+    last_refill: Instant,
+    delay: Delay,
+}
+
+impl<T> Throttle<T> {
+    pub fn new(inner: T, interval: Duration, burst: u32) -> Throttle<T> {
+        let burst = cmp::max(burst, 1);
+        let now = Instant::now();
+        Throttle {
+            inner,
+            interval,
+            burst,
+            tokens: burst,
+            last_refill: now,
+            delay: Delay::new(now),
+        }
+    }
+
+    /// Unpack the `Throttle`, returning the inner stream or sink.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        if self.tokens == self.burst {
+            self.last_refill = now;
+            return;
+        }
+
+        let interval_nanos = self.interval.as_nanos();
+        if interval_nanos == 0 {
+            self.tokens = self.burst;
+            self.last_refill = now;
+            return;
+        }
+
+        let new_tokens = (now - self.last_refill).as_nanos() / interval_nanos;
+        if new_tokens >= u128::from(self.burst - self.tokens) {
+            self.tokens = self.burst;
+            self.last_refill = now;
+        } else {
+            let new_tokens = new_tokens as u32;
+            self.tokens += new_tokens;
+            self.last_refill += self.interval * new_tokens;
+        }
+    }
+
+    /// Check whether there is a token available. If not, the current task will be notified when
+    /// there is.
+    fn poll_token(&mut self) -> Async<()> {
+        loop {
+            self.refill();
+            if self.tokens > 0 {
+                return Async::Ready(());
+            }
+
+            self.delay.reset(self.last_refill + self.interval);
+            if let Async::NotReady = self.delay.poll().void_unwrap() {
+                return Async::NotReady;
+            }
+        }
+    }
+}
+
+impl<T: Stream> Stream for Throttle<T> {
+    type Item = T::Item;
+    type Error = T::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<T::Item>>, T::Error> {
+        if let Async::NotReady = self.poll_token() {
+            return Ok(Async::NotReady);
+        }
+
+        match self.inner.poll()? {
+            Async::Ready(Some(x)) => {
+                self.tokens -= 1;
+                Ok(Async::Ready(Some(x)))
+            },
+            Async::Ready(None) => Ok(Async::Ready(None)),
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl<T: Sink> Sink for Throttle<T> {
+    type SinkItem = T::SinkItem;
+    type SinkError = T::SinkError;
+
+    fn start_send(&mut self, item: T::SinkItem) -> Result<AsyncSink<T::SinkItem>, T::SinkError> {
+        if let Async::NotReady = self.poll_token() {
+            return Ok(AsyncSink::NotReady(item));
+        }
+
+        let res = self.inner.start_send(item)?;
+        if let AsyncSink::Ready = res {
+            self.tokens -= 1;
+        }
+        Ok(res)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, T::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Result<Async<()>, T::SinkError> {
+        self.inner.close()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::stream;
+    use tokio;
+    use StreamExt;
+
+    #[test]
+    fn refill_tokens() {
+        let interval = Duration::from_millis(10);
+        let mut throttle = Throttle::new((), interval, 5);
+
+        let last_refill = Instant::now() - Duration::from_millis(25);
+        throttle.tokens = 0;
+        throttle.last_refill = last_refill;
+        throttle.refill();
+        assert_eq!(throttle.tokens, 2);
+        assert_eq!(throttle.last_refill, last_refill + interval * 2);
+
+        throttle.last_refill = Instant::now() - Duration::from_secs(1);
+        throttle.refill();
+        assert_eq!(throttle.tokens, 5);
+    }
+
+    #[test]
+    fn burst_then_steady_rate() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let interval = Duration::from_millis(50);
+        let started = Instant::now();
+        let stream = {
+            stream::iter_ok::<_, ()>(0..6)
+            .throttle(interval, 3)
+            .map(|_| Instant::now() - started)
+        };
+        let times = unwrap!(runtime.block_on(stream.collect()));
+        assert!(times[2] < interval);
+        for (i, time) in times.iter().enumerate().skip(3) {
+            assert!(*time >= interval * (i as u32 - 2));
+        }
+    }
+}
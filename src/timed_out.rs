@@ -0,0 +1,41 @@
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Error produced when a future or stream times out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut {
+    deadline: Instant,
+    elapsed: Duration,
+}
+
+impl TimedOut {
+    pub fn new(deadline: Instant, elapsed: Duration) -> TimedOut {
+        TimedOut {
+            deadline,
+            elapsed,
+        }
+    }
+
+    /// The instant at which the timeout expired.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// How long the future or stream had been running for when it timed out.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "timed out after {:?}", self.elapsed)
+    }
+}
+
+impl Error for TimedOut {
+    fn description(&self) -> &str {
+        "timed out"
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+use futures::{Async, Future, Stream};
+use delay::Delay;
+use timed_out::TimedOut;
+use void::ResultVoidExt;
+
+/// Runs a future or stream until a deadline, failing with the error produced by a callback if the
+/// deadline is reached.
+pub struct TimeoutOrElse<F, G> {
+    inner: F,
+    delay: Delay,
+    started: Instant,
+    deadline: Instant,
+    on_timeout: Option<G>,
+}
+
+impl<F, G> TimeoutOrElse<F, G> {
+    /// Creates a new `TimeoutOrElse` which runs `inner` for the given duration.
+    pub fn new(inner: F, duration: Duration, on_timeout: G) -> TimeoutOrElse<F, G> {
+        TimeoutOrElse::new_at(inner, Instant::now() + duration, on_timeout)
+    }
+
+    /// Creates a new `TimeoutOrElse` which runs `inner` until the given instant.
+    pub fn new_at(inner: F, instant: Instant, on_timeout: G) -> TimeoutOrElse<F, G> {
+        TimeoutOrElse {
+            inner,
+            delay: Delay::new(instant),
+            started: Instant::now(),
+            deadline: instant,
+            on_timeout: Some(on_timeout),
+        }
+    }
+
+    /// Unpack the `TimeoutOrElse`, returning the inner future or stream.
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+
+    fn timed_out(&self) -> TimedOut {
+        TimedOut::new(self.deadline, self.started.elapsed())
+    }
+}
+
+impl<F, G> Future for TimeoutOrElse<F, G>
+where
+    F: Future,
+    G: FnOnce(TimedOut) -> F::Error,
+{
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Result<Async<F::Item>, F::Error> {
+        if let Async::Ready(()) = self.delay.poll().void_unwrap() {
+            let timed_out = self.timed_out();
+            let on_timeout = unwrap!(self.on_timeout.take());
+            return Err(on_timeout(timed_out));
+        }
+
+        self.inner.poll()
+    }
+}
+
+impl<F, G> Stream for TimeoutOrElse<F, G>
+where
+    F: Stream,
+    G: FnOnce(TimedOut) -> F::Error,
+{
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<F::Item>>, F::Error> {
+        if let Async::Ready(()) = self.delay.poll().void_unwrap() {
+            let timed_out = self.timed_out();
+            return match self.on_timeout.take() {
+                Some(on_timeout) => Err(on_timeout(timed_out)),
+                None => Ok(Async::Ready(None)),
+            };
+        }
+
+        self.inner.poll()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{future, stream};
+    use tokio;
+    use FutureExt;
+    use StreamExt;
+
+    #[test]
+    fn future_times_out() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let duration = Duration::from_millis(10);
+        let res = runtime.block_on(future::empty::<(), TimedOut>().timeout_err(duration));
+        let timed_out = res.unwrap_err();
+        assert!(timed_out.elapsed() >= duration);
+
+        let res = runtime.block_on({
+            future::ok::<_, &str>(123)
+            .timeout_or_else(Duration::from_secs(10), |_| "timed out")
+        });
+        assert_eq!(res, Ok(123));
+    }
+
+    #[test]
+    fn stream_times_out() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let stream = {
+            stream::iter_ok::<_, &str>(vec![1, 2])
+            .chain(stream::poll_fn(|| Ok(Async::NotReady)))
+            .timeout_or_else(Duration::from_millis(10), |_| "timed out")
+            .then(Ok::<_, ()>)
+        };
+        let res = unwrap!(runtime.block_on(stream.collect()));
+        assert_eq!(res, vec![Ok(1), Ok(2), Err("timed out")]);
+    }
+}
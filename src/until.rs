@@ -1,9 +1,10 @@
-use futures::{Async, Future, Stream};
+use futures::{Async, AsyncSink, Future, Stream, Sink};
 
-/// Runs a stream or future until some condition is met.
+/// Runs a stream, sink or future until some condition is met.
 pub struct Until<T, C> {
     orig: T,
     condition: C,
+    condition_met: bool,
 }
 
 impl<T, C> Until<T, C> {
@@ -11,10 +12,26 @@ impl<T, C> Until<T, C> {
         Until {
             orig,
             condition,
+            condition_met: false,
         }
     }
 }
 
+impl<T, C> Until<T, C>
+where
+    C: Future<Item=()>,
+{
+    /// Poll the condition, without polling it again once it has been met.
+    fn poll_condition(&mut self) -> Result<bool, C::Error> {
+        if !self.condition_met {
+            if let Async::Ready(()) = self.condition.poll()? {
+                self.condition_met = true;
+            }
+        }
+        Ok(self.condition_met)
+    }
+}
+
 impl<T, C> Future for Until<T, C>
 where
     T: Future,
@@ -52,3 +69,36 @@ where
     }
 }
 
+impl<T, C> Sink for Until<T, C>
+where
+    T: Sink,
+    C: Future<Item=()>,
+    T::SinkError: From<C::Error>
+{
+    type SinkItem = T::SinkItem;
+    type SinkError = T::SinkError;
+
+    fn start_send(&mut self, item: T::SinkItem) -> Result<AsyncSink<T::SinkItem>, T::SinkError> {
+        if self.poll_condition()? {
+            return Ok(AsyncSink::Ready);
+        }
+
+        self.orig.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, T::SinkError> {
+        if self.poll_condition()? {
+            return Ok(Async::Ready(()));
+        }
+
+        self.orig.poll_complete()
+    }
+
+    fn close(&mut self) -> Result<Async<()>, T::SinkError> {
+        if self.poll_condition()? {
+            return Ok(Async::Ready(()));
+        }
+
+        self.orig.close()
+    }
+}
@@ -87,6 +87,159 @@ where
     }
 }
 
+/// Like `WhileDriving`, but fails immediately if the future being driven fails. Created using
+/// `FutureExt::while_driving_fail_fast`.
+pub struct WhileDrivingFailFast<A, B: Future> {
+    inner: WhileDrivingInner<A, B>,
+}
+
+impl<A, B: Future> WhileDrivingFailFast<A, B> {
+    pub fn new(a: A, b: B) -> WhileDrivingFailFast<A, B> {
+        WhileDrivingFailFast {
+            inner: WhileDrivingInner::Driving(a, b),
+        }
+    }
+}
+
+impl<A, B> Future for WhileDrivingFailFast<A, B>
+where
+    A: Future,
+    B: Future,
+{
+    type Item = (A::Item, Finish<B>);
+    type Error = WhileDrivingError<A, B>;
+
+    fn poll(&mut self) -> Result<Async<(A::Item, Finish<B>)>, WhileDrivingError<A, B>> {
+        let inner = mem::replace(&mut self.inner, WhileDrivingInner::Finished);
+        match inner {
+            WhileDrivingInner::Driving(mut a, mut b) => {
+                match a.poll() {
+                    Ok(Async::Ready(x)) => {
+                        let finish = Finish {
+                            state: FinishState::Inner(FinishInner::Running(b)),
+                        };
+                        Ok(Async::Ready((x, finish)))
+                    },
+                    Ok(Async::NotReady) => {
+                        match b.poll() {
+                            Ok(Async::Ready(x)) => {
+                                self.inner = WhileDrivingInner::Drove(a, Ok(x));
+                                Ok(Async::NotReady)
+                            },
+                            Ok(Async::NotReady) => {
+                                self.inner = WhileDrivingInner::Driving(a, b);
+                                Ok(Async::NotReady)
+                            },
+                            Err(e) => Err(WhileDrivingError::Driven(a, e)),
+                        }
+                    },
+                    Err(e) => {
+                        let finish = Finish {
+                            state: FinishState::Inner(FinishInner::Running(b)),
+                        };
+                        Err(WhileDrivingError::Main(e, finish))
+                    },
+                }
+            },
+            WhileDrivingInner::Drove(mut a, res) => {
+                match a.poll() {
+                    Ok(Async::Ready(x)) => {
+                        let finish = Finish {
+                            state: FinishState::Inner(FinishInner::Ran(res)),
+                        };
+                        Ok(Async::Ready((x, finish)))
+                    },
+                    Ok(Async::NotReady) => {
+                        self.inner = WhileDrivingInner::Drove(a, res);
+                        Ok(Async::NotReady)
+                    },
+                    Err(e) => {
+                        let finish = Finish {
+                            state: FinishState::Inner(FinishInner::Ran(res)),
+                        };
+                        Err(WhileDrivingError::Main(e, finish))
+                    },
+                }
+            },
+            WhileDrivingInner::Finished => {
+                panic!("poll() called on WhileDrivingFailFast which has already finished");
+            },
+        }
+    }
+}
+
+/// Like `WhileDriving`, but finishes as soon as either future finishes. Created using
+/// `FutureExt::while_driving_eager`.
+pub struct WhileDrivingEager<A, B> {
+    inner: Option<(A, B)>,
+}
+
+impl<A, B> WhileDrivingEager<A, B> {
+    pub fn new(a: A, b: B) -> WhileDrivingEager<A, B> {
+        WhileDrivingEager {
+            inner: Some((a, b)),
+        }
+    }
+}
+
+impl<A, B> Future for WhileDrivingEager<A, B>
+where
+    A: Future,
+    B: Future,
+{
+    type Item = WhileDrivingItem<A, B>;
+    type Error = WhileDrivingError<A, B>;
+
+    fn poll(&mut self) -> Result<Async<WhileDrivingItem<A, B>>, WhileDrivingError<A, B>> {
+        let (mut a, mut b) = match self.inner.take() {
+            Some(inner) => inner,
+            None => panic!("poll() called on WhileDrivingEager which has already finished"),
+        };
+        match a.poll() {
+            Ok(Async::Ready(x)) => {
+                let finish = Finish {
+                    state: FinishState::Inner(FinishInner::Running(b)),
+                };
+                Ok(Async::Ready(WhileDrivingItem::Main(x, finish)))
+            },
+            Ok(Async::NotReady) => {
+                match b.poll() {
+                    Ok(Async::Ready(x)) => Ok(Async::Ready(WhileDrivingItem::Driven(a, x))),
+                    Ok(Async::NotReady) => {
+                        self.inner = Some((a, b));
+                        Ok(Async::NotReady)
+                    },
+                    Err(e) => Err(WhileDrivingError::Driven(a, e)),
+                }
+            },
+            Err(e) => {
+                let finish = Finish {
+                    state: FinishState::Inner(FinishInner::Running(b)),
+                };
+                Err(WhileDrivingError::Main(e, finish))
+            },
+        }
+    }
+}
+
+/// The value yielded by `WhileDrivingEager`.
+pub enum WhileDrivingItem<A: Future, B: Future> {
+    /// The main future finished. Contains its value and the future it was driving.
+    Main(A::Item, Finish<B>),
+    /// The driven future finished first. Contains the still-running main future and the value of
+    /// the driven future.
+    Driven(A, B::Item),
+}
+
+/// The error returned by `WhileDrivingFailFast` and `WhileDrivingEager`.
+pub enum WhileDrivingError<A: Future, B: Future> {
+    /// The main future failed. Contains its error and the future it was driving.
+    Main(A::Error, Finish<B>),
+    /// The driven future failed first. Contains the still-running main future and the error of
+    /// the driven future.
+    Driven(A, B::Error),
+}
+
 /// Future yielded by `WhileDriving` which wraps the future it was driving. Can be resolved to
 /// whatever the original future would resolved to or unpacked using `into_inner`.
 pub struct Finish<B: Future> {
@@ -143,3 +296,52 @@ impl<B: Future> Future for Finish<B> {
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::future;
+    use std::time::{Duration, Instant};
+    use tokio;
+    use delay::Delay;
+    use FutureExt;
+
+    #[test]
+    fn fail_fast_on_driven_error() {
+        let main = future::empty::<(), u32>();
+        let res = main.while_driving_fail_fast(future::err::<(), u32>(5)).wait();
+        match res {
+            Err(WhileDrivingError::Driven(_, 5)) => (),
+            _ => panic!("expected the driven future's error"),
+        }
+    }
+
+    #[test]
+    fn fail_fast_keeps_driven_success() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let main = {
+            Delay::new(Instant::now() + Duration::from_millis(10))
+            .map(|()| 1)
+            .infallible::<u32>()
+        };
+        let res = runtime.block_on(main.while_driving_fail_fast(future::ok::<u32, u32>(2)));
+        let (x, finish) = match res {
+            Ok(x) => x,
+            Err(..) => panic!("unexpected error"),
+        };
+        assert_eq!(x, 1);
+        match finish.into_inner() {
+            FinishInner::Ran(Ok(2)) => (),
+            _ => panic!("expected the driven future to have finished"),
+        }
+    }
+
+    #[test]
+    fn eager_surfaces_driven_success() {
+        let main = future::empty::<(), u32>();
+        let res = main.while_driving_eager(future::ok::<u32, u32>(3)).wait();
+        match res {
+            Ok(WhileDrivingItem::Driven(_, 3)) => (),
+            _ => panic!("expected the driven future's value"),
+        }
+    }
+}
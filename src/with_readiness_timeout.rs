@@ -1,5 +1,6 @@
 use std::time::{Instant, Duration};
 use Delay;
+use timed_out::TimedOut;
 use futures::{Future, Stream, Async};
 use void::ResultVoidExt;
 
@@ -40,3 +41,140 @@ impl<S: Stream> Stream for WithReadinessTimeout<S> {
     }
 }
 
+/// Like `WithReadinessTimeout`, but yields a `TimedOut` error instead of ending the stream. The
+/// timer is restarted after the error, so the stream can continue to be used.
+pub struct WithReadinessTimeoutErr<S> {
+    stream: S,
+    duration: Duration,
+    delay: Delay,
+    last_ready: Instant,
+}
+
+impl<S> WithReadinessTimeoutErr<S> {
+    pub fn new(stream: S, duration: Duration) -> WithReadinessTimeoutErr<S> {
+        let last_ready = Instant::now();
+        let delay = Delay::new(last_ready + duration);
+        WithReadinessTimeoutErr {
+            stream, duration, delay, last_ready,
+        }
+    }
+
+    fn restart(&mut self) {
+        self.last_ready = Instant::now();
+        self.delay.reset(self.last_ready + self.duration);
+    }
+}
+
+impl<S> Stream for WithReadinessTimeoutErr<S>
+where
+    S: Stream,
+    S::Error: From<TimedOut>,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<S::Item>>, S::Error> {
+        match self.stream.poll() {
+            Ok(Async::Ready(Some(item))) => {
+                self.restart();
+                Ok(Async::Ready(Some(item)))
+            }
+            Ok(Async::Ready(None)) => Ok(Async::Ready(None)),
+            Ok(Async::NotReady) => {
+                match self.delay.poll().void_unwrap() {
+                    Async::Ready(()) => {
+                        let timed_out = TimedOut::new(
+                            self.last_ready + self.duration,
+                            self.last_ready.elapsed(),
+                        );
+                        self.restart();
+                        Err(S::Error::from(timed_out))
+                    },
+                    Async::NotReady => Ok(Async::NotReady),
+                }
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Wraps a stream and yields a heartbeat item whenever the stream goes for a given duration
+/// without yielding an item.
+pub struct WithHeartbeat<S, F> {
+    stream: S,
+    duration: Duration,
+    delay: Delay,
+    heartbeat: F,
+}
+
+impl<S, F> WithHeartbeat<S, F> {
+    pub fn new(stream: S, duration: Duration, heartbeat: F) -> WithHeartbeat<S, F> {
+        let delay = Delay::new(Instant::now() + duration);
+        WithHeartbeat {
+            stream, duration, delay, heartbeat,
+        }
+    }
+}
+
+impl<S, F> Stream for WithHeartbeat<S, F>
+where
+    S: Stream,
+    F: FnMut() -> S::Item,
+{
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Result<Async<Option<S::Item>>, S::Error> {
+        match self.stream.poll() {
+            Ok(Async::Ready(Some(item))) => {
+                self.delay.reset(Instant::now() + self.duration);
+                Ok(Async::Ready(Some(item)))
+            }
+            Ok(Async::Ready(None)) => Ok(Async::Ready(None)),
+            Ok(Async::NotReady) => {
+                match self.delay.poll().void_unwrap() {
+                    Async::Ready(()) => {
+                        self.delay.reset(Instant::now() + self.duration);
+                        Ok(Async::Ready(Some((self.heartbeat)())))
+                    },
+                    Async::NotReady => Ok(Async::NotReady),
+                }
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio;
+    use test_util::timed_stream;
+    use StreamExt;
+
+    #[test]
+    fn readiness_timeout_err_continues_after_error() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Instant::now();
+        let items = [(0, 1), (50, 2)];
+        let stream = {
+            timed_stream::<_, TimedOut>(started, &items)
+            .with_readiness_timeout_err(Duration::from_millis(30))
+            .then(|res| Ok::<_, ()>(res.map_err(|_| ())))
+        };
+        let res = unwrap!(runtime.block_on(stream.collect()));
+        assert_eq!(res, vec![Ok(1), Err(()), Ok(2)]);
+    }
+
+    #[test]
+    fn heartbeat_injected_when_idle() {
+        let mut runtime = unwrap!(tokio::runtime::current_thread::Runtime::new());
+        let started = Instant::now();
+        let items = [(0, 1), (50, 2)];
+        let stream = {
+            timed_stream::<_, ()>(started, &items)
+            .with_heartbeat(Duration::from_millis(30), || 0)
+        };
+        assert_eq!(unwrap!(runtime.block_on(stream.collect())), vec![1, 0, 2]);
+    }
+}
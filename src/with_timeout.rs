@@ -1,5 +1,5 @@
 use std::time::{Duration, Instant};
-use futures::{Async, Future, Stream};
+use futures::{Async, AsyncSink, Future, Stream, Sink};
 use delay::Delay;
 use void::ResultVoidExt;
 
@@ -26,7 +26,7 @@ impl<F> WithTimeout<F> {
         }
     }
 
-    /// Unpack the `WithTimeout`, returning the inner future or stream.
+    /// Unpack the `WithTimeout`, returning the inner future, stream or sink.
     pub fn into_inner(self) -> F {
         self.inner
     }
@@ -68,3 +68,34 @@ where
     }
 }
 
+impl<F> Sink for WithTimeout<F>
+where
+    F: Sink
+{
+    type SinkItem = F::SinkItem;
+    type SinkError = F::SinkError;
+
+    fn start_send(&mut self, item: F::SinkItem) -> Result<AsyncSink<F::SinkItem>, F::SinkError> {
+        if let Async::Ready(()) = self.delay.poll().void_unwrap() {
+            return Ok(AsyncSink::Ready);
+        }
+
+        self.inner.start_send(item)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, F::SinkError> {
+        if let Async::Ready(()) = self.delay.poll().void_unwrap() {
+            return Ok(Async::Ready(()));
+        }
+
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Result<Async<()>, F::SinkError> {
+        if let Async::Ready(()) = self.delay.poll().void_unwrap() {
+            return Ok(Async::Ready(()));
+        }
+
+        self.inner.close()
+    }
+}